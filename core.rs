@@ -1,9 +1,14 @@
 //! Core movement types, constants, and systems
-use avian3d::debug_render::{PhysicsGizmos, PhysicsGizmoExt};
+use avian3d::debug_render::PhysicsGizmos;
 use avian3d::prelude::*;
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 pub use crate::game::util::{print_parent_chain, sanity_check_transforms};
+pub use crate::game::player::movement::step::{accelerate, apply_friction};
+use crate::game::player::movement::step::{
+    apply_impulse, apply_input, check_water_jump, is_walkable, kinematic_step, update_crouch, MovementInput,
+    MovementState, OptionalMovementComponents, SpatialCollisionWorld,
+};
 
 /// Marker component indicating whether the player is grounded.
 ///
@@ -11,11 +16,32 @@ pub use crate::game::util::{print_parent_chain, sanity_check_transforms};
 #[derive(Component, Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct Grounded(pub bool);
 
+/// Component indicating whether the player is crouching.
+///
+/// `Crouching(true)` lowers the top of the player body to `CROUCH_BODY_FULL_HEIGHT` and scales ground speed
+/// by `MovementConfig::crouch_speed_scale`. Stays true while a ceiling prevents standing up.
+#[derive(Component, Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Crouching(pub bool);
+
+/// Movement input that `NetworkedPlayerInput` doesn't carry, written by the input layer next to it each tick.
+///
+/// Must be replicated alongside `NetworkedPlayerInput` for server-side movement. Players without it never crouch
+/// and climb ladders as if looking straight ahead.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ExtraMovementInput {
+    /// Whether crouch is held this tick.
+    pub crouch_pressed: bool,
+    /// View pitch (radians, positive looking up), used for climbing ladders.
+    pub pitch_rotation: f32,
+}
+
 /// Resource containing all movement configuration parameters for the player.
 ///
 /// Used as a Bevy resource and can be loaded from a RON config file.
+/// Fields missing from the file keep their default values.
 #[derive(Resource, Reflect, Serialize, Deserialize, Clone, Debug)]
 #[reflect(Resource)]
+#[serde(default)]
 pub struct MovementConfig {
     /// Maximum player speed (units/sec).
     pub speed: f32,
@@ -35,6 +61,111 @@ pub struct MovementConfig {
     pub step_speed_multiplier: f32,
     /// Minimum speed for step climbing.
     pub min_step_speed: f32,
+    /// How the player walks up steps.
+    pub step_mode: StepMode,
+    /// Multiplier applied to ground speed while crouching.
+    pub crouch_speed_scale: f32,
+    /// Air movement model used while airborne.
+    pub air_model: AirMovementModel,
+    /// Maximum wish speed while airborne for `Qw`, and for strafe-only input with `Cpm` (Quake's 30 u/s).
+    pub air_wish_speed_cap: f32,
+    /// Acceleration when braking against current velocity in air (`Cpm` only).
+    pub air_stop_accelerate: f32,
+    /// Acceleration for strafe-only input in air (`Cpm` only).
+    pub strafe_accelerate: f32,
+    /// Strength of forward-input air steering (`Cpm` only).
+    pub air_control: f32,
+    /// When jump input triggers a jump.
+    pub jump_mode: JumpMode,
+    /// Seconds a jump press is remembered before landing (jump buffering). Unused by `JumpMode::Queued`.
+    pub jump_buffer_time: f32,
+    /// Seconds after walking off a ledge during which a jump is still allowed (coyote time).
+    pub coyote_time: f32,
+    /// Steepest ground angle (degrees from horizontal) the player can stand on.
+    pub max_slope_angle: f32,
+    /// Counter-Strike-style surfing: slopes steeper than `max_slope_angle` are slid along frictionlessly instead of
+    /// being treated as walls.
+    pub surf_enabled: bool,
+    /// Upward speed along a ramp (units/sec) above which landing on it keeps the player sliding (rampslide).
+    pub rampslide_speed: f32,
+    /// Upward speed along a ramp (units/sec) above which running up it launches the player (trimp).
+    pub trimp_speed: f32,
+    /// Multiplier applied to the upward ramp speed when trimping.
+    pub trimp_multiplier: f32,
+    /// Knockback speed (units/sec) per point of damage, see `MovementImpulse::knockback`.
+    pub knockback_per_damage: f32,
+    /// Damage above which knockback no longer grows.
+    pub max_knockback_damage: f32,
+    /// Multiplier for impulses a player causes to themselves (rocket jumps).
+    pub self_impulse_scale: f32,
+    /// Whether crouching at speed starts a crouch-slide.
+    pub slide_enabled: bool,
+    /// Minimum horizontal speed (units/sec) for crouching to start a slide.
+    pub slide_min_speed: f32,
+    /// Friction applied while sliding, instead of `friction`.
+    pub slide_friction: f32,
+    /// Multiplier for gravity pulling the player down slopes while sliding.
+    pub slide_slope_scale: f32,
+    /// Maximum slide duration (seconds).
+    pub slide_duration: f32,
+    /// Seconds after a slide ends before another one can start.
+    pub slide_cooldown: f32,
+    /// Multiplier applied to `jump_power` when jumping out of a slide.
+    pub slide_jump_scale: f32,
+    /// Seconds after landing from a jump during which jumping again gets `double_jump_power` (CPMA double jump).
+    pub double_jump_window: f32,
+    /// Extra upward velocity for a double jump. 0.0 disables double jumps.
+    pub double_jump_power: f32,
+    /// CPMA ramp jump: jumping while moving up a ramp adds the upward speed to the jump.
+    pub ramp_jump: bool,
+    /// Whether jumping shortly after touching a wall in the air kicks off it.
+    pub wall_jump_enabled: bool,
+    /// Seconds after touching a wall during which a wall jump is allowed.
+    pub wall_jump_window: f32,
+    /// Upward velocity applied by a wall jump.
+    pub wall_jump_power: f32,
+    /// Velocity applied away from the wall by a wall jump.
+    pub wall_jump_push: f32,
+    /// Whether touching a wall at speed while airborne starts a wall run.
+    pub wall_run_enabled: bool,
+    /// Minimum horizontal speed (units/sec) to start or keep a wall run.
+    pub wall_run_min_speed: f32,
+    /// Maximum time (seconds) spent wall running between landings.
+    pub wall_run_max_duration: f32,
+    /// Multiplier applied to gravity while wall running.
+    pub wall_run_gravity_scale: f32,
+    /// Climbing speed on ladders (units/sec).
+    pub ladder_climb_speed: f32,
+    /// Velocity applied away from the ladder by a dismount jump.
+    pub ladder_jump_push: f32,
+    /// Upward velocity applied by a dismount jump.
+    pub ladder_jump_power: f32,
+    /// Multiplier applied to `speed` while swimming.
+    pub swim_speed_scale: f32,
+    /// Acceleration while swimming.
+    pub water_accelerate: f32,
+    /// Drag applied on all axes while swimming, instead of `friction`.
+    pub water_friction: f32,
+    /// Fraction of gravity cancelled by buoyancy while swimming (1.0 floats in place).
+    pub liquid_buoyancy: f32,
+    /// Speed (units/sec) at which a swimming player without input sinks.
+    pub swim_sink_speed: f32,
+    /// Forward velocity applied when jumping out of a liquid onto a ledge.
+    pub water_jump_push: f32,
+    /// Upward velocity applied when jumping out of a liquid onto a ledge.
+    pub water_jump_power: f32,
+    /// Whether hard landings send `FallDamage`.
+    pub fall_damage_enabled: bool,
+    /// Impact speed (units/sec) below which landings do no damage.
+    pub fall_damage_min_speed: f32,
+    /// Impact speed (units/sec) at which fall damage reaches `fall_damage_max`.
+    pub fall_damage_max_speed: f32,
+    /// Damage at `fall_damage_min_speed`.
+    pub fall_damage_min: f32,
+    /// Damage at and above `fall_damage_max_speed`.
+    pub fall_damage_max: f32,
+    /// Shape of the damage curve between the two speeds (1.0 is linear).
+    pub fall_damage_exponent: f32,
 }
 impl Default for MovementConfig {
     fn default() -> Self {
@@ -48,10 +179,340 @@ impl Default for MovementConfig {
             step_climb_speed: 1.5,
             step_speed_multiplier: 0.3,
             min_step_speed: 1.0,
+            step_mode: StepMode::Smoothed,
+            crouch_speed_scale: 0.5,
+            air_model: AirMovementModel::Vq3,
+            air_wish_speed_cap: 0.66,
+            air_stop_accelerate: 2.5,
+            strafe_accelerate: 70.0,
+            air_control: 150.0,
+            jump_mode: JumpMode::HoldAutoHop,
+            jump_buffer_time: 0.1,
+            coyote_time: 0.1,
+            max_slope_angle: 45.0,
+            surf_enabled: false,
+            rampslide_speed: 4.0,
+            trimp_speed: 9.0,
+            trimp_multiplier: 1.0,
+            knockback_per_damage: 0.15,
+            max_knockback_damage: 200.0,
+            self_impulse_scale: 1.0,
+            slide_enabled: false,
+            slide_min_speed: 6.0,
+            slide_friction: 0.5,
+            slide_slope_scale: 1.0,
+            slide_duration: 1.0,
+            slide_cooldown: 0.5,
+            slide_jump_scale: 1.0,
+            double_jump_window: 0.4,
+            double_jump_power: 0.0,
+            ramp_jump: false,
+            wall_jump_enabled: false,
+            wall_jump_window: 0.15,
+            wall_jump_power: 5.0,
+            wall_jump_push: 4.0,
+            wall_run_enabled: false,
+            wall_run_min_speed: 5.0,
+            wall_run_max_duration: 1.5,
+            wall_run_gravity_scale: 0.2,
+            ladder_climb_speed: 3.5,
+            ladder_jump_push: 4.0,
+            ladder_jump_power: 2.0,
+            swim_speed_scale: 0.5,
+            water_accelerate: 4.0,
+            water_friction: 1.0,
+            liquid_buoyancy: 1.0,
+            swim_sink_speed: 1.3,
+            water_jump_push: 3.7,
+            water_jump_power: 6.5,
+            fall_damage_enabled: false,
+            fall_damage_min_speed: 10.0,
+            fall_damage_max_speed: 25.0,
+            fall_damage_min: 5.0,
+            fall_damage_max: 100.0,
+            fall_damage_exponent: 2.0,
         }
     }
 }
 
+impl MovementConfig {
+    /// Minimum Y component of a surface normal for it to count as walkable ground.
+    pub fn min_walkable_normal_y(&self) -> f32 {
+        self.max_slope_angle.to_radians().cos()
+    }
+}
+
+/// Jump mode, selecting when jump input triggers a jump. Lets gamemodes pick competitive or casual behaviour.
+#[derive(Reflect, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum JumpMode {
+    /// Only a fresh press jumps; holding jump never re-triggers.
+    PressEdge,
+    /// Holding jump hops again on every landing.
+    #[default]
+    HoldAutoHop,
+    /// A press in the air is queued and fires on the next landing, however long the fall.
+    Queued,
+}
+
+/// Step mode, selecting how the player walks up steps.
+#[derive(Reflect, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StepMode {
+    /// The body floats `MAX_STEP_HEIGHT` above the ground and the ground cast raises it over steps at
+    /// `step_climb_speed`, with the extended collider catching ledges while airborne.
+    #[default]
+    Smoothed,
+    /// Quake's `PM_StepSlideMove`: the body reaches down to the feet, and a blocked move is retried stepped up by
+    /// the step height and back down, keeping whichever went further. Steps are climbed instantly, as in Quake maps.
+    StepSlide,
+}
+
+/// Air movement model, selecting how input accelerates the player while airborne.
+#[derive(Reflect, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AirMovementModel {
+    /// Quake 3 (vanilla): air acceleration only, no air control.
+    #[default]
+    Vq3,
+    /// Challenge ProMode: strafe acceleration, air braking and forward air control.
+    Cpm,
+    /// QuakeWorld: capped air wish speed, enabling classic air strafing.
+    Qw,
+}
+
+/// Ground contact information from the most recent ground cast.
+///
+/// Updated by `kinematic_movement`; `normal` is `Vec3::ZERO` while no ground is within step range.
+#[derive(Component, Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct GroundInfo {
+    /// Surface normal of the ground below the player.
+    pub normal: Vec3,
+    /// Whether the ground is shallow enough to stand on (see `MovementConfig::max_slope_angle`).
+    pub walkable: bool,
+    /// Entity (collider) hit by the ground cast, used to track moving platforms.
+    #[serde(skip)]
+    pub entity: Option<Entity>,
+    /// Velocity of the ground platform at the player's position, as applied this tick.
+    pub platform_velocity: Vec3,
+    /// Height (meters) still to be climbed by step smoothing, 0.0 if not stepping.
+    pub step_offset: f32,
+}
+
+/// Event pushing a player, e.g. weapon knockback. Consumed by `kinematic_movement` on the next fixed tick.
+///
+/// Impulses are added to `CharacterVelocity`; upward impulses unground the player (see `step::apply_impulse`).
+#[derive(Event, Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MovementImpulse {
+    /// The player to push.
+    pub entity: Entity,
+    /// Velocity change (units/sec).
+    pub impulse: Vec3,
+    /// Entity that caused the impulse. When it is the pushed player, `MovementConfig::self_impulse_scale` applies.
+    #[serde(skip)]
+    pub source: Option<Entity>,
+}
+
+impl MovementImpulse {
+    /// Quake-style knockback: pushes along `direction` proportionally to `damage`, capped at
+    /// `MovementConfig::max_knockback_damage`.
+    pub fn knockback(entity: Entity, source: Option<Entity>, direction: Vec3, damage: f32, config: &MovementConfig) -> Self {
+        let strength = damage.clamp(0.0, config.max_knockback_damage) * config.knockback_per_damage;
+        Self { entity, impulse: direction.normalize_or_zero() * strength, source }
+    }
+}
+
+/// Selects which component the kinematic controller reads and writes the player position from.
+///
+/// Both backends run the same `step` simulation; they only differ in where the result is stored.
+/// Add it as a plugin (or insert it as a resource) to choose a backend; defaults to `Transform` when absent.
+#[derive(Resource, Reflect, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[reflect(Resource)]
+pub enum MovementBackend {
+    /// Move the player by writing `Transform::translation`.
+    #[default]
+    Transform,
+    /// Move the player by writing Avian's `Position`, letting Avian sync the `Transform`.
+    Position,
+}
+
+impl MovementBackend {
+    /// Returns the player's current translation for this backend.
+    ///
+    /// Falls back to the `Transform` if the `Position` backend is selected but the entity has no `Position`.
+    pub fn translation(self, transform: &Transform, position: Option<&Position>) -> Vec3 {
+        match (self, position) {
+            (MovementBackend::Position, Some(position)) => position.0,
+            _ => transform.translation,
+        }
+    }
+
+    /// Stores the player's new translation for this backend.
+    pub fn write_translation(self, translation: Vec3, transform: &mut Transform, position: Option<&mut Position>) {
+        match (self, position) {
+            (MovementBackend::Position, Some(position)) => position.0 = translation,
+            _ => transform.translation = translation,
+        }
+    }
+}
+
+/// Adding a backend as a plugin selects it, e.g. `app.add_plugins(MovementBackend::Position)`.
+///
+/// Also adds `MovementCorePlugin` if it isn't already, so the core systems have their events.
+impl Plugin for MovementBackend {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<MovementCorePlugin>() {
+            app.add_plugins(MovementCorePlugin);
+        }
+        app.register_type::<MovementBackend>().insert_resource(*self);
+    }
+}
+
+/// Registers the events read and written by `movement` and `kinematic_movement`.
+///
+/// Added by `MovementBackend` and `MovementEventsPlugin`; add it directly when using neither.
+pub struct MovementCorePlugin;
+
+impl Plugin for MovementCorePlugin {
+    fn build(&self, app: &mut App) {
+        use crate::game::player::movement::events::{HitWall, Jumped, Landed, LeftGround, SteppedUp};
+        app.add_event::<MovementImpulse>()
+            .add_event::<Jumped>()
+            .add_event::<Landed>()
+            .add_event::<LeftGround>()
+            .add_event::<SteppedUp>()
+            .add_event::<HitWall>();
+    }
+}
+
+/// Selects the shape of the player body used by penetration correction, slide casts and ground casts.
+///
+/// Insert as a resource to choose a shape; defaults to `Cylinder` when absent. Casts are never rotated, so `Aabb`
+/// and `QuakeAabb` stay axis-aligned like Quake's player hull.
+#[derive(Resource, Reflect, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[reflect(Resource)]
+pub enum PlayerShape {
+    /// Upright cylinder with a flat bottom.
+    #[default]
+    Cylinder,
+    /// Capsule, with rounded ends that slide over edges.
+    Capsule,
+    /// Axis-aligned box with the regular player dimensions.
+    Aabb,
+    /// Axis-aligned box with Quake's player hull dimensions, converted to meters by `scale` (meters per Quake unit).
+    ///
+    /// Uses Quake's 30x30 footprint, 56 unit standing and 40 unit crouched height and 18 unit steps, and casts for
+    /// the ground with the full hull footprint, so corners catch and players can stand on edges as in Quake.
+    /// Speeds in `MovementConfig` are not scaled; set them to the Quake values times `scale` to match.
+    QuakeAabb {
+        /// Meters per Quake unit.
+        scale: f32,
+    },
+}
+
+impl PlayerShape {
+    /// Quake hull with the common conversion of 32 Quake units per meter.
+    pub fn quake() -> Self {
+        PlayerShape::QuakeAabb { scale: QUAKE_UNIT_SCALE }
+    }
+
+    /// Returns the body dimensions for this shape.
+    pub fn hull(self) -> PlayerHull {
+        match self {
+            PlayerShape::QuakeAabb { scale } => PlayerHull::quake(scale),
+            _ => PlayerHull::default(),
+        }
+    }
+
+    /// Returns a collider of this shape with the given radius (half width) and full height, centered on its origin.
+    ///
+    /// Capsules shorter than their diameter shrink their radius so the total height is kept.
+    pub fn collider(self, radius: f32, height: f32) -> Collider {
+        match self {
+            PlayerShape::Cylinder => Collider::cylinder(radius, height),
+            PlayerShape::Capsule => {
+                let radius = radius.min(height * 0.5);
+                Collider::capsule(radius, height - 2.0 * radius)
+            }
+            PlayerShape::Aabb | PlayerShape::QuakeAabb { .. } => Collider::cuboid(radius * 2.0, height, radius * 2.0),
+        }
+    }
+
+    /// Returns the thin shape used for ground casts, and the height above the cast start to place its center at.
+    ///
+    /// The offset puts the lowest point of every shape at the same height, so cast distances mean the same for
+    /// all shapes and the step logic doesn't depend on the shape.
+    pub fn ground_cast_collider(self) -> (Collider, f32) {
+        let radius = self.hull().ground_cast_radius;
+        match self {
+            PlayerShape::Capsule => (Collider::sphere(radius), radius - GROUND_CAST_HALF_HEIGHT * 0.5),
+            _ => (self.collider(radius, GROUND_CAST_HALF_HEIGHT), 0.0),
+        }
+    }
+}
+
+/// Meters per Quake unit used by `PlayerShape::quake`.
+pub const QUAKE_UNIT_SCALE: f32 = 1.0 / 32.0;
+
+/// Player body dimensions (meters) used by the movement casts, selected through `PlayerShape::hull`.
+///
+/// The defaults are the `PLAYER_HEIGHT`, `BODY_RADIUS`, `CROUCH_PLAYER_HEIGHT` and `MAX_STEP_HEIGHT` constants.
+#[derive(Reflect, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct PlayerHull {
+    /// Body radius, or half width for boxes.
+    pub radius: f32,
+    /// Radius (or half width) of the ground cast.
+    pub ground_cast_radius: f32,
+    /// Standing player height, feet to top of head.
+    pub height: f32,
+    /// Crouched player height.
+    pub crouch_height: f32,
+    /// Highest step the player walks up without jumping.
+    pub step_height: f32,
+}
+
+impl Default for PlayerHull {
+    fn default() -> Self {
+        Self {
+            radius: BODY_RADIUS,
+            ground_cast_radius: GROUND_CAST_RADIUS,
+            height: PLAYER_HEIGHT,
+            crouch_height: CROUCH_PLAYER_HEIGHT,
+            step_height: MAX_STEP_HEIGHT,
+        }
+    }
+}
+
+impl PlayerHull {
+    /// Quake's player hull (mins -15 -15 -24, maxs 15 15 32, 40 units tall crouched, 18 unit steps) in meters.
+    pub fn quake(scale: f32) -> Self {
+        Self {
+            radius: 15.0 * scale,
+            ground_cast_radius: 15.0 * scale,
+            height: 56.0 * scale,
+            crouch_height: 40.0 * scale,
+            step_height: 18.0 * scale,
+        }
+    }
+
+    /// Height of the body collider, which floats `step_height` above the feet (see `BODY_FULL_HEIGHT`).
+    pub fn body_full_height(&self) -> f32 {
+        self.height - self.step_height - GROUND_CAST_HALF_HEIGHT
+    }
+
+    /// Height of the body collider while crouching (see `CROUCH_BODY_FULL_HEIGHT`).
+    pub fn crouch_body_full_height(&self) -> f32 {
+        self.crouch_height - self.step_height - GROUND_CAST_HALF_HEIGHT
+    }
+
+    /// How much further down the extended airborne collider reaches (see `EXTENDED_STEP_HEIGHT`).
+    pub fn extended_step_height(&self) -> f32 {
+        self.step_height * 0.95
+    }
+
+    /// Height of the feet below a body centered at `translation`.
+    pub fn feet(&self, translation: Vec3) -> f32 {
+        translation.y - self.body_full_height() * 0.5 - self.step_height - GROUND_CAST_HALF_HEIGHT
+    }
+}
+
 /// Player velocity (in world space).
 ///
 /// The inner `Vec3` represents the player's current velocity in world coordinates.
@@ -71,10 +532,10 @@ pub struct StickyWishDir(pub Vec3);
 pub struct TotalVelocity(pub Vec3);
 
 // Player body/collider constants
-/// Player capsule height (meters).
+/// Player height (meters).
 /// Used for all collision and cast calculations.
 pub const PLAYER_HEIGHT: f32 = 1.85;
-/// Player capsule radius (meters).
+/// Player body radius, or half width for `PlayerShape::Aabb` (meters).
 /// Used for all collision and cast calculations.
 pub const BODY_RADIUS: f32 = 0.42;
 /// Half-height of ground cast cylinder (meters).
@@ -84,14 +545,20 @@ pub const GROUND_CAST_HALF_HEIGHT: f32 = 0.01;
 /// The maximum vertical distance the player can step up.
 pub const MAX_STEP_HEIGHT: f32 = 0.55;
 /// Half-height of player body (meters).
-/// Used for capsule collider calculations.
+/// Used for body collider calculations.
 pub const BODY_HALF_HEIGHT: f32 = (PLAYER_HEIGHT - MAX_STEP_HEIGHT - GROUND_CAST_HALF_HEIGHT) * 0.5;
 /// Full height of player body (meters).
-/// Used for capsule collider calculations.
+/// Used for body collider calculations.
 pub const BODY_FULL_HEIGHT: f32 = BODY_HALF_HEIGHT * 2.0;
 /// Radius of ground cast cylinder (meters).
 /// Used for ground detection raycasts.
 pub const GROUND_CAST_RADIUS: f32 = BODY_RADIUS * 0.9;
+/// Player height while crouching (meters).
+/// Crouching lowers the top of the body; the feet and ground cast stay where they are.
+pub const CROUCH_PLAYER_HEIGHT: f32 = 1.3;
+/// Full height of player body while crouching (meters).
+/// Used for body collider calculations.
+pub const CROUCH_BODY_FULL_HEIGHT: f32 = CROUCH_PLAYER_HEIGHT - MAX_STEP_HEIGHT - GROUND_CAST_HALF_HEIGHT;
 /// Extended step height (meters).
 /// Slightly shorter than the maximum step height, used for extended collider.
 pub const EXTENDED_STEP_HEIGHT: f32 = MAX_STEP_HEIGHT * 0.95;
@@ -101,11 +568,17 @@ pub const EXTENDED_BODY_FULL_HEIGHT: f32 = BODY_FULL_HEIGHT + EXTENDED_STEP_HEIG
 
 /// Main player movement system. Handles input, friction, acceleration, and jumping.
 ///
-/// - Reads input from NetworkedPlayerInput and updates velocity based on ground/air state.
+/// - Reads input from NetworkedPlayerInput and ExtraMovementInput and updates velocity based on ground/air state.
 /// - Applies friction and acceleration.
-/// - Handles jump input and sets vertical velocity.
+/// - Handles jump input and sets vertical velocity, sending `Jumped`.
+/// - Handles crouch input, staying crouched while there is no room to stand.
+/// - Starts and ends crouch-slides, replacing friction and acceleration while sliding.
+/// - Handles wall jumps and starts and ends wall runs.
+/// - Moves players on a ladder along their view direction instead, with a dismount jump.
+/// - Swims players at least waist-deep in a liquid, and jumps them out onto ledges.
+/// - Runs for every player entity, so duels, bots and server-side proxies all move independently.
 ///
-/// Should be run in `FixedUpdate`.
+/// Wraps `step::update_crouch`, `step::check_water_jump` and `step::apply_input`. Should be run in `FixedUpdate`.
 pub fn movement(
     mut query: Query<(
         &mut CharacterVelocity,
@@ -115,64 +588,109 @@ pub fn movement(
         Option<&mut crate::game::player::movement::jump::JustJumped>,
         Option<&mut TotalVelocity>,
         Entity,
+        &Transform,
+        Option<&Position>,
+        &Children,
+        Option<&mut Crouching>,
+        (
+            Option<&crate::game::player::movement::jump::FallTimer>,
+            Option<&mut crate::game::player::movement::jump::JumpInputTimer>,
+            Option<&GroundInfo>,
+            Option<&mut crate::game::player::movement::jump::SlideTimer>,
+            Option<&mut crate::game::player::movement::wall::WallContact>,
+            Option<&mut crate::game::player::movement::wall::WallRunTimer>,
+            Option<&crate::game::player::movement::triggers::LadderContact>,
+            Option<&crate::game::player::movement::liquid::LiquidContact>,
+            Option<&ExtraMovementInput>,
+        ),
     ), With<crate::game::player::Player>>,
     mut commands: Commands,
+    spatial_query: SpatialQuery,
+    mut jumped: EventWriter<crate::game::player::movement::events::Jumped>,
     config: Res<MovementConfig>,
+    backend: Option<Res<MovementBackend>>,
+    shape: Option<Res<PlayerShape>>,
     time: Res<Time>,
 ) {
-    let Ok((mut velocity, networked_input, mut grounded, sticky_wish_dir, mut just_jumped, total_velocity, entity)) = query.get_single_mut() else {
-        return;
-    };
-
-    // Read movement input from networked input component instead of ActionState
-    let wish_vel = Vec3::new(networked_input.movement_direction.x, 0.0, -networked_input.movement_direction.y).normalize_or_zero();
-    // Use networked yaw rotation for consistent movement direction calculation across clients
-    let wish_dir = (Quat::from_rotation_y(networked_input.yaw_rotation) * wish_vel).normalize_or_zero();
-    let wish_speed = networked_input.movement_magnitude * config.speed;
+    let backend = backend.map_or_else(MovementBackend::default, |b| *b);
+    let shape = shape.map_or_else(PlayerShape::default, |s| *s);
+    for (mut velocity, networked_input, mut grounded, sticky_wish_dir, mut just_jumped, total_velocity, entity, transform, position, children, crouching, (fall_timer, jump_input, ground_info, slide, wall_contact, wall_run, ladder, liquid, extra_input)) in query.iter_mut() {
+        let world = SpatialCollisionWorld::new(&spatial_query, std::iter::once(entity).chain(children.iter()));
+        let input = MovementInput::from_components(networked_input, extra_input);
+        let mut state = MovementState::from_components(backend.translation(transform, position), &velocity, &grounded, just_jumped.as_deref(), fall_timer);
+        state.load_optional_components(OptionalMovementComponents {
+            crouching: crouching.as_deref(),
+            jump_input: jump_input.as_deref(),
+            ground_info,
+            slide: slide.as_deref(),
+            wall_contact: wall_contact.as_deref(),
+            wall_run: wall_run.as_deref(),
+            ladder,
+            liquid,
+        });
+        state.shape = shape;
+        update_crouch(&mut state, &input, &world);
+        check_water_jump(&mut state, &input, &config, &world);
+        let wish_dir = apply_input(&mut state, &input, &config, time.delta_secs());
+        state.write_components(&mut velocity, &mut grounded, just_jumped.as_deref_mut(), None);
+        if let Some(kind) = state.jumped {
+            jumped.write(crate::game::player::movement::events::Jumped { entity, kind, velocity: state.velocity });
+        }
 
-    // Sticky wish_dir logic
-    if wish_dir.length_squared() > 0.001 {
-        if let Some(mut sticky) = sticky_wish_dir {
-            sticky.0 = wish_dir;
+        if let Some(mut crouching) = crouching {
+            crouching.0 = state.crouching;
+        } else if state.crouching {
+            commands.entity(entity).insert(Crouching(true));
+        }
+        if let Some(mut jump_input) = jump_input {
+            jump_input.timer = state.jump_input_timer;
+            jump_input.held = state.jump_held;
         } else {
-            commands.entity(entity).insert(StickyWishDir(wish_dir));
+            commands.entity(entity).insert(crate::game::player::movement::jump::JumpInputTimer {
+                timer: state.jump_input_timer,
+                held: state.jump_held,
+            });
+        }
+        let slide_state = crate::game::player::movement::jump::SlideTimer {
+            sliding: state.sliding,
+            timer: state.slide_timer,
+            cooldown: state.slide_cooldown,
+        };
+        if let Some(mut slide) = slide {
+            *slide = slide_state;
+        } else if state.sliding {
+            commands.entity(entity).insert(slide_state);
+        }
+        if let Some(mut wall_contact) = wall_contact {
+            wall_contact.timer = state.wall_timer;
+        }
+        let wall_run_state = crate::game::player::movement::wall::WallRunTimer {
+            running: state.wall_running,
+            timer: state.wall_run_timer,
+        };
+        if let Some(mut wall_run) = wall_run {
+            *wall_run = wall_run_state;
+        } else if state.wall_running {
+            commands.entity(entity).insert(wall_run_state);
         }
-    }
 
-    // Instead of directly modifying velocity.0, accumulate into total_velocity
-    let mut frame_delta = Vec3::ZERO;
-    if grounded.0 {
-        apply_friction(&mut velocity.0, &config, &time);
-        accelerate(
-            &mut velocity.0,
-            wish_dir,
-            wish_speed,
-            config.ground_accelerate,
-            &time,
-        );
-    } else {
-        accelerate(
-            &mut velocity.0,
-            wish_dir,
-            wish_speed,
-            config.air_accelerate,
-            &time,
-        );
-    }
-    // Read jump input from networked input component instead of ActionState
-    if networked_input.jump_pressed && grounded.0 {
-        velocity.0.y = config.jump_power;
-        grounded.0 = false; // Immediately become ungrounded when jumping
-        // Reset JustJumped timer on jump
-        if let Some(jj) = just_jumped.as_deref_mut() {
-            jj.timer = 0.0;
+        // Sticky wish_dir logic
+        if wish_dir.length_squared() > 0.001 {
+            if let Some(mut sticky) = sticky_wish_dir {
+                sticky.0 = wish_dir;
+            } else {
+                commands.entity(entity).insert(StickyWishDir(wish_dir));
+            }
+        }
+
+        // Instead of directly modifying velocity.0, accumulate into total_velocity
+        let mut frame_delta = Vec3::ZERO;
+        frame_delta += velocity.0;
+        if let Some(mut total) = total_velocity {
+            total.0 += frame_delta;
+        } else {
+            commands.entity(entity).insert(TotalVelocity(frame_delta));
         }
-    }
-    frame_delta += velocity.0;
-    if let Some(mut total) = total_velocity {
-        total.0 += frame_delta;
-    } else {
-        commands.entity(entity).insert(TotalVelocity(frame_delta));
     }
 }
 
@@ -181,8 +699,16 @@ pub fn movement(
 /// - Applies gravity if not grounded.
 /// - Performs ground/step detection and normalization.
 /// - Handles collision and sliding along surfaces.
+/// - Applies pending `MovementImpulse`s, ungrounding the player on upward knockback.
+/// - Sends `Landed`, `LeftGround`, `SteppedUp` and `HitWall` from the step's event reports.
+/// - Updates `GroundInfo` with the ground normal and ground entity.
+/// - Updates `WallContact` with the last wall hit, and reduces gravity while wall running.
+/// - Skips gravity for players climbing a ladder, and applies buoyancy to swimming players.
+/// - Carries grounded players along with the moving platform (any body with `LinearVelocity`) they stand on.
+/// - Reads and writes the player position through the selected `MovementBackend`.
+/// - Casts with the selected `PlayerShape`.
 ///
-/// Should be run in `FixedUpdate` after `movement`.
+/// Wraps `step::kinematic_step`. Should be run in `FixedUpdate` after `movement`.
 pub fn kinematic_movement(
     mut query: Query<
         (
@@ -191,47 +717,91 @@ pub fn kinematic_movement(
             &mut CharacterVelocity,
             &Children,
             &mut Grounded,
-            Option<&crate::game::player::movement::jump::JustJumped>,
+            Option<&mut crate::game::player::movement::jump::JustJumped>,
             Option<&crate::game::player::movement::jump::FallTimer>,
             Option<&crate::game::player::movement::jump::PreviousFallTimer>,
             Option<&mut TotalVelocity>,
+            Option<&mut Position>,
+            Option<&Crouching>,
+            Option<&mut GroundInfo>,
+            (
+                Option<&crate::game::player::movement::jump::JumpInputTimer>,
+                Option<&crate::game::player::movement::jump::SlideTimer>,
+                Option<&mut crate::game::player::movement::wall::WallContact>,
+                Option<&crate::game::player::movement::wall::WallRunTimer>,
+                Option<&crate::game::player::movement::triggers::LadderContact>,
+                Option<&crate::game::player::movement::liquid::LiquidContact>,
+            ),
         ),
         With<crate::game::player::Player>,
     >,
+    mut commands: Commands,
     spatial_query: SpatialQuery,
+    colliders: Query<&ColliderOf>,
+    platforms: Query<PlatformData, Without<crate::game::player::Player>>,
+    mut impulses: EventReader<MovementImpulse>,
+    mut events: MovementEventWriters,
     time: Res<Time>,
     config: Res<MovementConfig>,
+    backend: Option<Res<MovementBackend>>,
+    shape: Option<Res<PlayerShape>>,
     mut gizmos: Gizmos<'_, '_, PhysicsGizmos>,
 ) {
-    for (entity, mut transform, mut velocity, children, mut grounded, just_jumped, fall_timer, _, total_velocity) in query.iter_mut() {
+    let backend = backend.map_or_else(MovementBackend::default, |b| *b);
+    let shape = shape.map_or_else(PlayerShape::default, |s| *s);
+    let mut pending_impulses: std::collections::HashMap<Entity, Vec3> = std::collections::HashMap::new();
+    for event in impulses.read() {
+        let scale = if event.source == Some(event.entity) { config.self_impulse_scale } else { 1.0 };
+        *pending_impulses.entry(event.entity).or_default() += event.impulse * scale;
+    }
+    for (entity, mut transform, mut velocity, children, mut grounded, mut just_jumped, fall_timer, _, total_velocity, mut position, crouching, ground_info, (jump_input, slide, wall_contact, wall_run, ladder, liquid)) in query.iter_mut() {
         let mut frame_delta = Vec3::ZERO;
-        apply_gravity(&mut velocity, &grounded, &config, &time);
-        let ground_cast_distance = ground_and_step_normalization(
-            entity,
-            &mut transform,
-            &mut velocity,
-            &mut grounded,
-            just_jumped,
-            fall_timer,
-            &spatial_query,
-            &config,
-            &time,
-            &mut gizmos,
-        );
-        // Compute air_time for collision_and_slide
-        let air_time = fall_timer.map_or(0.0, |f| f.timer);
-        collision_and_slide(
-            entity,
-            &mut transform,
-            &mut velocity,
-            &children,
-            &mut grounded,
-            &spatial_query,
-            &time,
-            air_time,
-            ground_cast_distance,
-            &mut gizmos,
-        );
+        let world = SpatialCollisionWorld::new(&spatial_query, std::iter::once(entity).chain(children.iter()));
+        let translation = backend.translation(&transform, position.as_deref());
+        let mut state = MovementState::from_components(translation, &velocity, &grounded, just_jumped.as_deref(), fall_timer);
+        state.load_optional_components(OptionalMovementComponents {
+            crouching,
+            jump_input,
+            ground_info: ground_info.as_deref(),
+            slide,
+            wall_contact: wall_contact.as_deref(),
+            wall_run,
+            ladder,
+            liquid,
+        });
+        state.shape = shape;
+        if let Some(impulse) = pending_impulses.remove(&entity) {
+            apply_impulse(&mut state, impulse);
+        }
+        // The platform may have moved since last tick, so sample its velocity at the player's current position
+        state.platform_velocity = platform_velocity_at(state.ground_entity, translation, &colliders, &platforms);
+        kinematic_step(&mut state, &config, time.delta_secs(), &world);
+        backend.write_translation(state.translation, &mut transform, position.as_deref_mut());
+        state.write_components(&mut velocity, &mut grounded, just_jumped.as_deref_mut(), None);
+        events.send_kinematic(entity, &state);
+        let ground = GroundInfo {
+            normal: state.ground_normal,
+            walkable: state.ground_normal != Vec3::ZERO && is_walkable(state.ground_normal, &config),
+            entity: state.ground_entity,
+            platform_velocity: state.platform_velocity,
+            step_offset: state.step_offset,
+        };
+        if let Some(mut ground_info) = ground_info {
+            *ground_info = ground;
+        } else {
+            commands.entity(entity).insert(ground);
+        }
+        let wall = crate::game::player::movement::wall::WallContact {
+            normal: state.wall_normal,
+            entity: state.wall_entity,
+            timer: state.wall_timer,
+        };
+        if let Some(mut wall_contact) = wall_contact {
+            *wall_contact = wall;
+        } else if state.wall_timer < f32::MAX {
+            commands.entity(entity).insert(wall);
+        }
+        world.draw_debug(&mut gizmos);
         frame_delta += velocity.0;
         if let Some(mut total) = total_velocity {
             total.0 += frame_delta;
@@ -239,425 +809,62 @@ pub fn kinematic_movement(
     }
 }
 
-/// Applies gravity to the player if not grounded.
-fn apply_gravity(velocity: &mut CharacterVelocity, grounded: &Grounded, config: &MovementConfig, time: &Time) {
-    if !grounded.0 {
-        velocity.0.y -= config.gravity * time.delta_secs();
-    }
+/// Event writers for the movement events sent by `kinematic_movement`.
+#[derive(bevy::ecs::system::SystemParam)]
+pub struct MovementEventWriters<'w> {
+    landed: EventWriter<'w, crate::game::player::movement::events::Landed>,
+    left_ground: EventWriter<'w, crate::game::player::movement::events::LeftGround>,
+    stepped_up: EventWriter<'w, crate::game::player::movement::events::SteppedUp>,
+    hit_wall: EventWriter<'w, crate::game::player::movement::events::HitWall>,
 }
 
-/// Handles ground detection and step normalization, including debug shape emission.
-fn ground_and_step_normalization(
-    entity: Entity,
-    transform: &mut Transform,
-    velocity: &mut CharacterVelocity,
-    grounded: &mut Grounded,
-    just_jumped: Option<&crate::game::player::movement::jump::JustJumped>,
-    fall_timer: Option<&crate::game::player::movement::jump::FallTimer>,
-    spatial_query: &SpatialQuery,
-    config: &MovementConfig,
-    time: &Time,
-    gizmos: &mut Gizmos<'_, '_, PhysicsGizmos>,
-) -> Option<f32> {
-    // Use only the jump timer to determine if we should skip ground logic
-    let just_jumped_active = just_jumped.map_or(false, |j| j.timer < 0.05); // Increased threshold for proper jump grace period
-    let mut ground_cast_distance = None;
-    if !just_jumped_active {
-        if crate::game::player::movement::debug::is_debug_shape_casts() {
-            let cast_start = transform.translation - Vec3::Y * (BODY_FULL_HEIGHT * 0.5);
-            let cast_direction = Dir3::NEG_Y;
-            let max_step_distance = MAX_STEP_HEIGHT * 2.0;
-            let ground_shape = Collider::cylinder(GROUND_CAST_RADIUS, GROUND_CAST_HALF_HEIGHT);
-            let filter = SpatialQueryFilter::from_excluded_entities(vec![entity]);
-            let cast_config = ShapeCastConfig {
-                max_distance: max_step_distance,
-                ..Default::default()
-            };
-            let ground_hit = spatial_query.cast_shape(
-                &ground_shape,
-                cast_start,
-                Quat::default(),
-                cast_direction,
-                &cast_config,
-                &filter,
-            );
-            // Use Avian3D's built-in debug rendering
-            gizmos.draw_shapecast(
-                &ground_shape,
-                cast_start,
-                Quat::default(),
-                cast_direction,
-                max_step_distance,
-                &[],
-                Color::srgb(0.0, 1.0, 1.0), // ray_color
-                Color::srgb(1.0, 1.0, 0.0), // shape_color
-                Color::srgb(1.0, 0.0, 0.0), // point_color
-                Color::srgb(0.0, 1.0, 0.0), // normal_color
-                1.0,
-            );
+impl MovementEventWriters<'_> {
+    /// Sends the events reported by `step::kinematic_step` in `state`.
+    fn send_kinematic(&mut self, entity: Entity, state: &MovementState) {
+        use crate::game::player::movement::events::{HitWall, Landed, LeftGround, SteppedUp};
+        if let Some(landing) = state.landing {
+            self.landed.write(Landed { entity, impact_speed: landing.impact_speed, air_time: landing.air_time });
         }
-    }
-    // Movement logic
-    if just_jumped_active {
-        grounded.0 = false;
-    } else {
-        // Always compute the cast origin relative to the player body collider center.
-        // The cast origin is the center of the player collider minus half the collider height on Y.
-        // This ensures the ground cast is always aligned with the collider, regardless of state.
-        let collider_center = transform.translation; // Assumes transform.translation is the collider center
-        let cast_start = collider_center - Vec3::Y * (BODY_FULL_HEIGHT * 0.5);
-        let cast_direction = Dir3::NEG_Y;
-        let max_step_distance = MAX_STEP_HEIGHT * 2.0;
-        let ground_shape = Collider::cylinder(GROUND_CAST_RADIUS, GROUND_CAST_HALF_HEIGHT);
-        let filter = SpatialQueryFilter::from_excluded_entities(vec![entity]);
-        let cast_config = ShapeCastConfig {
-            max_distance: max_step_distance,
-            ..Default::default()
-        };
-        let ground_hit = spatial_query.cast_shape(
-            &ground_shape,
-            cast_start,
-            Quat::default(),
-            cast_direction,
-            &cast_config,
-            &filter,
-        );
-        if let Some(hit) = &ground_hit {
-            ground_cast_distance = Some(hit.distance);
-            let diff = MAX_STEP_HEIGHT - hit.distance;
-            let air_time = fall_timer.map_or(0.0, |f| f.timer);
-            let base_landing_threshold = 0.015;
-            let dynamic_landing_threshold = velocity.0.y.abs() * time.delta_secs() + base_landing_threshold;
-            // --- FIX: Robust landing logic ---
-            if hit.distance <= MAX_STEP_HEIGHT && air_time > 0.05 {
-                // Snap player so ground cast distance is exactly MAX_STEP_HEIGHT
-                let correction = cast_direction * (hit.distance - MAX_STEP_HEIGHT);
-                transform.translation += correction;
-                velocity.0.y = 0.0;
-                grounded.0 = true;
-            } else if air_time <= 0.01 && diff.abs() > 0.01 {
-                // Only allow step smoothing if not falling
-                let horizontal_speed = velocity.0.xz().length();
-                let calculated_step_speed = config.step_climb_speed + (horizontal_speed * horizontal_speed * 0.07);
-                let dynamic_step_speed = calculated_step_speed.max(config.min_step_speed);
-                let max_step_delta = dynamic_step_speed * time.delta_secs();
-                let movement_to_apply = if diff > 0.0 {
-                    diff.min(max_step_delta)
-                } else {
-                    diff.max(-max_step_delta)
-                };
-                velocity.0.y = movement_to_apply / time.delta_secs();
-            } else if diff.abs() <= 0.01 {
-                velocity.0.y = 0.0;
-                grounded.0 = true;
-            }
-        } else {
-            // If no ground hit, grounded.0 should be true if ground_cast_distance is Some and <= MAX_STEP_HEIGHT
-            if let Some(dist) = ground_cast_distance {
-                grounded.0 = dist <= MAX_STEP_HEIGHT;
-            } else {
-                grounded.0 = false;
-            }
+        if state.left_ground {
+            self.left_ground.write(LeftGround { entity, velocity: state.velocity });
         }
-    }
-    ground_cast_distance
-}
-
-/// Performs collision detection and sliding for the player capsule.
-///
-/// This function:
-/// - Applies robust penetration correction (magenta debug shape when airborne, red when grounded).
-/// - Performs a shape cast for collide-and-slide (yellow debug shape), using the same center and height as the collider.
-/// - Ensures debug shapes and shape casts are visually and logically aligned in both grounded and airborne states.
-/// - Prevents velocity snapping/zeroing when moving upward (only applies when falling).
-/// - All debug shapes are offset by `-Vec3::Y * (cast_height * 0.5)` for correct mesh alignment.
-///
-/// # Parameters
-/// - `entity`: The player entity.
-/// - `transform`: The player's transform (mutated in-place).
-/// - `velocity`: The player's velocity (mutated in-place).
-/// - `children`: The player's child entities (for exclusion in collision).
-/// - `grounded`: Whether the player is grounded (mutated in-place).
-/// - `spatial_query`: The spatial query interface for collision.
-/// - `time`: Time resource for delta time.
-/// - `air_time`: Time spent airborne (used for airborne logic).
-/// - `debug_shape_events`: Event writer for debug shape emission.
-fn collision_and_slide(
-    entity: Entity,
-    transform: &mut Transform,
-    velocity: &mut CharacterVelocity,
-    children: &Children,
-    grounded: &mut Grounded,
-    spatial_query: &SpatialQuery,
-    time: &Time,
-    air_time: f32,
-    ground_cast_distance: Option<f32>,
-    gizmos: &mut Gizmos<'_, '_, PhysicsGizmos>,
-) {
-    // --- Begin robust collide-and-slide logic (reference-inspired) ---
-    const EPSILON: f32 = 1e-5; // Increased for more robust overlap detection
-    const MAX_COLLISION_ITERATIONS: u32 = 5;
-    const PENETRATION_OFFSET: f32 = 0.002; // Smaller offset to reduce jitter
-    let mut iterations = 0;
-    let mut vel = velocity.0 * time.delta_secs();
-
-    // Penetration correction (Linahan)
-    let mut excluded_entities = vec![entity];
-    excluded_entities.extend(children.iter());
-    let filter = SpatialQueryFilter::from_excluded_entities(excluded_entities.clone());
-    // Use the extended collider for penetration correction and debug shapes
-    let use_extended_collider = should_use_extended_collider(air_time, grounded, velocity, ground_cast_distance);
-    if crate::game::player::movement::debug::is_debug_shape_casts() {
-        // Show the penetration correction collider (magenta if airborne, red if grounded)
-        let (color, center, height) = if use_extended_collider {
-            (
-                Color::srgb(1.0, 0.0, 1.0),
-                transform.translation - Vec3::Y * (EXTENDED_STEP_HEIGHT * 0.5),
-                EXTENDED_BODY_FULL_HEIGHT,
-            )
-        } else {
-            (
-                Color::srgb(1.0, 0.0, 0.0),
-                transform.translation,
-                BODY_FULL_HEIGHT,
-            )
-        };
-        let collider = Collider::cylinder(BODY_RADIUS, height);
-        gizmos.draw_shapecast(
-            &collider,
-            center - Vec3::Y * (height * 0.5),
-            Quat::default(),
-            Dir3::Y,
-            0.0,
-            &[],
-            color,
-            Color::srgb(1.0, 1.0, 0.0),
-            Color::srgb(1.0, 0.0, 0.0),
-            Color::srgb(0.0, 1.0, 0.0),
-            1.0,
-        );
-    }
-    let (collider, collider_origin) = if use_extended_collider {
-        (
-            Collider::cylinder(BODY_RADIUS, EXTENDED_BODY_FULL_HEIGHT),
-            transform.translation - Vec3::Y * (EXTENDED_STEP_HEIGHT * 0.5),
-        )
-    } else {
-        (
-            Collider::cylinder(BODY_RADIUS, BODY_FULL_HEIGHT),
-            transform.translation,
-        )
-    };
-    let contacts = spatial_query.shape_hits(
-        &collider,
-        collider_origin,
-        transform.rotation,
-        Dir3::NEG_Y,
-        8, // max_results: u32 (was 0.0 for distance)
-        &ShapeCastConfig { max_distance: 0.0, ..Default::default() },
-        &filter,
-    );
-    if !contacts.is_empty() {
-        let mut max_penetration = 0.0;
-        let mut correction = Vec3::ZERO;
-        for contact in &contacts {
-            // avian3d 0.3.x: use contact.distance instead of time_of_impact
-            if contact.distance < -EPSILON && -contact.distance > max_penetration {
-                max_penetration = -contact.distance;
-                correction = contact.normal2 * (max_penetration + PENETRATION_OFFSET);
-            }
+        if let Some(height) = state.stepped_up {
+            self.stepped_up.write(SteppedUp { entity, height });
         }
-        if max_penetration > EPSILON {
-            transform.translation += correction;
-        }
-    }
-
-    // Use the extended collider for collision/slide debug and shape casts
-    let use_extended_collider = should_use_extended_collider(air_time, grounded, velocity, ground_cast_distance);
-    while vel.length() > EPSILON && iterations < MAX_COLLISION_ITERATIONS {
-        let direction = vel.normalize_or_zero();
-        let distance = vel.length();
-        if direction.length_squared() == 0.0 {
-            break;
-        }
-        let filter = SpatialQueryFilter::from_excluded_entities(excluded_entities.clone());
-        if crate::game::player::movement::debug::is_debug_shape_casts() {
-            let (cast_origin, cast_height) = if use_extended_collider {
-                (
-                    transform.translation - Vec3::Y * (EXTENDED_STEP_HEIGHT * 0.5),
-                    EXTENDED_BODY_FULL_HEIGHT,
-                )
-            } else {
-                (
-                    transform.translation,
-                    BODY_FULL_HEIGHT,
-                )
-            };
-            let cast_collider = Collider::cylinder(BODY_RADIUS, cast_height);
-            gizmos.draw_shapecast(
-                &cast_collider,
-                cast_origin - Vec3::Y * (cast_height * 0.5),
-                Quat::default(),
-                Dir3::new_unchecked(direction),
-                distance,
-                &[],
-                Color::srgb(0.0, 1.0, 1.0), // ray_color
-                Color::srgb(1.0, 1.0, 0.0), // shape_color
-                Color::srgb(1.0, 0.0, 0.0), // point_color
-                Color::srgb(0.0, 1.0, 0.0), // normal_color
-                1.0,
-            );
-        }
-        let (cast_collider, cast_origin) = if use_extended_collider {
-            (
-                Collider::cylinder(BODY_RADIUS, EXTENDED_BODY_FULL_HEIGHT),
-                transform.translation - Vec3::Y * (EXTENDED_STEP_HEIGHT * 0.5),
-            )
-        } else {
-            (
-                Collider::cylinder(BODY_RADIUS, BODY_FULL_HEIGHT),
-                transform.translation,
-            )
-        };
-        let hits = spatial_query.shape_hits(
-            &cast_collider,
-            cast_origin,
-            transform.rotation,
-            Dir3::new_unchecked(direction),
-            8, // max_results: u32 (was distance * 1.1)
-            &ShapeCastConfig { max_distance: distance * 1.1, ..Default::default() },
-            &filter,
-        );
-        if !hits.is_empty() {
-            // Treat all collisions as a perfectly flat, straight vertical wall (use the horizontal component of the average normal)
-            let mut avg_normal = Vec3::ZERO;
-            let mut min_dist = f32::MAX;
-            let mut hit_count = 0;
-            let mut collision_point = transform.translation;
-            for hit in &hits {
-                if hit.distance < min_dist && hit.distance >= 0.0 {
-                    min_dist = hit.distance;
-                    collision_point = transform.translation + direction * hit.distance;
-                }
-                avg_normal += hit.normal1;
-                hit_count += 1;
-            }
-            // Flatten the normal: ignore Y, only use XZ (horizontal) component
-            let mut flat_normal = if hit_count > 0 {
-                let n = avg_normal / hit_count as f32;
-                Vec3::new(n.x, 0.0, n.z).normalize_or_zero()
-            } else {
-                Vec3::new(direction.x, 0.0, direction.z).normalize_or_zero()
-            };
-            // If the flat normal is zero (e.g. running into a perfectly vertical wall), fallback to X+
-            if flat_normal.length_squared() < EPSILON {
-                flat_normal = Vec3::X;
-            }
-            let collision_time = min_dist / distance;
-            if collision_time > 1.0 || collision_time < -EPSILON {
-                transform.translation += vel;
-                break;
-            }
-            transform.translation = collision_point + flat_normal * PENETRATION_OFFSET;
-            let destination = transform.translation + vel * (1.0 - collision_time);
-            let slide_distance = destination - transform.translation;
-            // Project velocity onto the plane perpendicular to the (flattened) collision normal
-            let slide_velocity = slide_distance - flat_normal * slide_distance.dot(flat_normal);
-            let original_speed = vel.length();
-            let slide_speed = slide_velocity.length();
-            let slide_velocity = if slide_speed > EPSILON {
-                slide_velocity * (original_speed / slide_speed).min(1.0)
-            } else {
-                Vec3::ZERO
-            };
-            // Quake 3–style velocity clipping: project velocity onto the plane perpendicular to the collision normal
-            let collision_normal = if hit_count > 0 {
-                (avg_normal / hit_count as f32).normalize_or_zero()
-            } else {
-                direction.normalize_or_zero()
-            };
-            let speed = velocity.0.dot(collision_normal);
-            if speed < 0.0 {
-                velocity.0 -= speed * collision_normal;
-            }
-            // Continue with slide logic as before
-            vel = slide_velocity;
-            if flat_normal.y > 0.7 && velocity.0.y < 0.0 {
-                grounded.0 = true;
-            }
-            iterations += 1;
-        } else {
-            transform.translation += vel;
-            break;
-        }
-    }
-    // Clamp Y for safety
-    if transform.translation.y < -100.0 { transform.translation.y = -100.0; }
-    if transform.translation.y > 1000.0 { transform.translation.y = 1000.0; }
-    assert!(transform.translation.is_finite(), "Player transform is not finite after movement: {:?}", transform.translation);
-    assert!(velocity.0.is_finite(), "Player velocity is not finite after movement: {:?}", velocity.0);
-    if grounded.0 {
-        velocity.0.y = 0.0;
-        let ground_y = (transform.translation.y / 0.01).round() * 0.01;
-        if (transform.translation.y - ground_y).abs() < 0.02 {
-            transform.translation.y = ground_y;
+        if let Some(wall_hit) = state.wall_hit {
+            self.hit_wall.write(HitWall { entity, normal: wall_hit.normal, speed: wall_hit.speed });
         }
     }
 }
 
-/// Accelerates the player velocity in the given wish direction.
-///
-/// # Parameters
-/// - `velocity`: The player's velocity vector (mutated in-place).
-/// - `wish_dir`: The desired movement direction (normalized).
-/// - `wish_speed`: The desired speed in the wish direction.
-/// - `accel`: Acceleration rate.
-/// - `time`: Time resource for delta time.
-pub fn accelerate(
-    velocity: &mut Vec3,
-    wish_dir: Vec3,
-    wish_speed: f32,
-    accel: f32,
-    time: &Res<Time>,
-) {
-    let current_speed = velocity.dot(wish_dir);
-    let add_speed = wish_speed - current_speed;
-
-    if add_speed <= 0.0 {
-        return;
-    }
-
-    let accel_speed = accel * wish_speed * time.delta_secs();
-    let accel_speed = accel_speed.min(add_speed);
-
-    velocity.x += accel_speed * wish_dir.x;
-    velocity.z += accel_speed * wish_dir.z;
-}
+/// Components of a body the player can stand on, used to inherit its motion.
+type PlatformData = (
+    &'static LinearVelocity,
+    Option<&'static AngularVelocity>,
+    &'static Position,
+    &'static Rotation,
+    Option<&'static ComputedCenterOfMass>,
+);
 
-/// Applies friction to the player velocity when grounded.
+/// Returns the velocity of the ground entity's rigid body at `point`, including its angular velocity.
 ///
-/// # Parameters
-/// - `velocity`: The player's velocity vector (mutated in-place).
-/// - `config`: The movement configuration.
-/// - `time`: Time resource for delta time.
-pub fn apply_friction(
-    velocity: &mut Vec3,
-    config: &MovementConfig,
-    time: &Res<Time>,
-) {
-    let speed = velocity.length();
-    if speed <= 0.0 {
-        return;
-    }
-
-    let drop = speed * config.friction * time.delta_secs();
-    let new_speed = (speed - drop).max(0.0);
-
-    if speed > 0.0 {
-        let speed_ratio = new_speed / speed;
-        velocity.x *= speed_ratio;
-        velocity.z *= speed_ratio;
-    }
+/// Static ground, or ground without a `LinearVelocity`, returns `Vec3::ZERO`.
+pub fn platform_velocity_at(
+    ground_entity: Option<Entity>,
+    point: Vec3,
+    colliders: &Query<&ColliderOf>,
+    platforms: &Query<PlatformData, Without<crate::game::player::Player>>,
+) -> Vec3 {
+    let Some(ground_entity) = ground_entity else {
+        return Vec3::ZERO;
+    };
+    // The ground cast hits colliders, which may be children of the moving body
+    let body = colliders.get(ground_entity).map_or(ground_entity, |collider_of| collider_of.body);
+    let Ok((linear, angular, position, rotation, center_of_mass)) = platforms.get(body) else {
+        return Vec3::ZERO;
+    };
+    let center = position.0 + rotation.0 * center_of_mass.map_or(Vec3::ZERO, |com| com.0);
+    linear.0 + angular.map_or(Vec3::ZERO, |a| a.0.cross(point - center))
 }
 
 /// Updates the WasGroundedLastFrame component for all players.
@@ -714,208 +921,110 @@ pub fn apply_total_velocity(
     }
 }
 
-// Returns true if the player should use the extended collider for collision/slide and penetration correction.
-// This is true if:
-// - The player is airborne for a while (air_time > 0.2)
-// - OR the player is not grounded and is moving downward (velocity.0.y < 0.0)
-// - AND the ground cast is None or >= MAX_STEP_HEIGHT
-fn should_use_extended_collider(
-    air_time: f32,
-    grounded: &Grounded,
-    velocity: &CharacterVelocity,
-    ground_cast_distance: Option<f32>,
-) -> bool {
-    // Only use extended collider if there is NO ground within step height, or the ground is farther than or equal to MAX_STEP_HEIGHT
-    let airborne = (air_time > 0.15) && (!grounded.0);
-    airborne
-}
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
 
-/// Position-based version of ground_and_step_normalization that works with Avian's Position component
-fn ground_and_step_normalization_position(
-    entity: Entity,
-    position: &mut Position,
-    velocity: &mut CharacterVelocity,
-    grounded: &mut Grounded,
-    just_jumped: Option<&crate::game::player::movement::jump::JustJumped>,
-    fall_timer: Option<&crate::game::player::movement::jump::FallTimer>,
-    spatial_query: &SpatialQuery,
-    config: &MovementConfig,
-    time: &Res<Time>,
-    _gizmos: &mut Gizmos<'_, '_, PhysicsGizmos>,
-) -> Option<f32> {
-    // Ground detection using Position instead of Transform
-    let ground_cast_origin = position.0 + Vec3::new(0.0, MAX_STEP_HEIGHT, 0.0);
-    let ground_cast_direction = Dir3::NEG_Y;
-    let ground_cast_distance = MAX_STEP_HEIGHT + GROUND_CAST_HALF_HEIGHT;
-    
-    let ground_collider = Collider::cylinder(GROUND_CAST_RADIUS, GROUND_CAST_HALF_HEIGHT);
-    let filter = SpatialQueryFilter::default().with_excluded_entities([entity]);
-    
-    if let Some(ground_hit) = spatial_query.cast_shape(
-        &ground_collider,
-        ground_cast_origin,
-        Quat::IDENTITY,
-        ground_cast_direction,
-        &ShapeCastConfig { max_distance: ground_cast_distance, ..Default::default() },
-        &filter,
-    ) {
-        let ground_distance = ground_hit.distance;
-        let ground_point = ground_cast_origin + ground_cast_direction.as_vec3() * ground_distance;
-        let ground_normal = ground_hit.normal1;
-        
-        // Update grounded state
-        if ground_distance <= MAX_STEP_HEIGHT && ground_normal.y > 0.7 {
-            grounded.0 = true;
-            
-            // Step up logic using Position
-            let target_y = ground_point.y + GROUND_CAST_HALF_HEIGHT;
-            if position.0.y < target_y {
-                position.0.y = target_y;
-            }
-            
-            // Zero out downward velocity when grounded
-            if velocity.0.y < 0.0 {
-                velocity.0.y = 0.0;
-            }
-        } else {
-            grounded.0 = false;
-        }
-        
-        Some(ground_distance)
-    } else {
-        grounded.0 = false;
-        None
+    use bevy::time::TimeUpdateStrategy;
+
+    use super::*;
+    use crate::game::player::input::NetworkedPlayerInput;
+
+    const DT: f32 = 1.0 / 64.0;
+
+    /// App running `movement` and `kinematic_movement` once per update at 64 Hz, in a world without colliders.
+    fn movement_app(backend: MovementBackend) -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, backend))
+            .init_gizmo_group::<PhysicsGizmos>()
+            .init_resource::<SpatialQueryPipeline>()
+            .init_resource::<MovementConfig>()
+            .insert_resource(Time::<Fixed>::from_seconds(DT as f64))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(DT)))
+            .add_systems(FixedUpdate, (movement, kinematic_movement).chain());
+        app
     }
-}
 
-/// Position-based version of collision_and_slide that works with Avian's Position component
-fn collision_and_slide_position(
-    entity: Entity,
-    position: &mut Position,
-    velocity: &mut CharacterVelocity,
-    _children: &Children,
-    grounded: &mut Grounded,
-    spatial_query: &SpatialQuery,
-    time: &Res<Time>,
-    air_time: f32,
-    ground_cast_distance: Option<f32>,
-    _gizmos: &mut Gizmos<'_, '_, PhysicsGizmos>,
-) {
-    const MAX_ITERATIONS: usize = 5;
-    const EPSILON: f32 = 0.001;
-    const PENETRATION_OFFSET: f32 = 0.002;
+    /// Spawns a player at `translation` (in both `Transform` and `Position`) walking along `direction`.
+    fn spawn_player(app: &mut App, translation: Vec3, direction: Vec2) -> Entity {
+        let input = NetworkedPlayerInput {
+            movement_direction: direction,
+            movement_magnitude: direction.length().min(1.0),
+            ..default()
+        };
+        app.world_mut()
+            .spawn((
+                crate::game::player::Player::default(),
+                input,
+                CharacterVelocity(Vec3::ZERO),
+                Grounded(false),
+                Transform::from_translation(translation),
+                Position(translation),
+                LinearVelocity::ZERO,
+            ))
+            .with_child(Transform::default())
+            .id()
+    }
 
-    let mut vel = velocity.0 * time.delta_secs();
-    let mut iterations = 0;
-    
-    // Choose collider based on air time and ground distance
-    let use_extended = should_use_extended_collider(air_time, grounded, velocity, ground_cast_distance);
-    let collider = if use_extended {
-        Collider::cylinder(BODY_RADIUS, EXTENDED_BODY_FULL_HEIGHT)
-    } else {
-        Collider::cylinder(BODY_RADIUS, BODY_FULL_HEIGHT)
-    };
-    
-    let filter = SpatialQueryFilter::default().with_excluded_entities([entity]);
-
-    while iterations < MAX_ITERATIONS && vel.length_squared() > EPSILON * EPSILON {
-        let direction = vel.normalize_or_zero();
-        let distance = vel.length();
-        
-        let cast_origin = position.0;
-        
-        let hits = spatial_query.shape_hits(
-            &collider,
-            cast_origin,
-            Quat::IDENTITY,
-            Dir3::new_unchecked(direction),
-            8, // max_results
-            &ShapeCastConfig { max_distance: distance * 1.1, ..Default::default() },
-            &filter,
-        );
-        
-        if !hits.is_empty() {
-            // Process collision using Position instead of Transform
-            let mut avg_normal = Vec3::ZERO;
-            let mut min_dist = f32::MAX;
-            let mut hit_count = 0;
-            let mut collision_point = position.0;
-            
-            for hit in &hits {
-                if hit.distance < min_dist && hit.distance >= 0.0 {
-                    min_dist = hit.distance;
-                    collision_point = position.0 + direction * hit.distance;
-                }
-                avg_normal += hit.normal1;
-                hit_count += 1;
-            }
-            
-            // Flatten the normal: ignore Y, only use XZ (horizontal) component
-            let mut flat_normal = if hit_count > 0 {
-                let n = avg_normal / hit_count as f32;
-                Vec3::new(n.x, 0.0, n.z).normalize_or_zero()
-            } else {
-                Vec3::new(direction.x, 0.0, direction.z).normalize_or_zero()
-            };
-            
-            if flat_normal.length_squared() < EPSILON {
-                flat_normal = Vec3::X;
-            }
-            
-            let collision_time = min_dist / distance;
-            if collision_time > 1.0 || collision_time < -EPSILON {
-                position.0 += vel;
-                break;
-            }
-            
-            position.0 = collision_point + flat_normal * PENETRATION_OFFSET;
-            let destination = position.0 + vel * (1.0 - collision_time);
-            let slide_distance = destination - position.0;
-            
-            // Project velocity onto the plane perpendicular to the (flattened) collision normal
-            let slide_velocity = slide_distance - flat_normal * slide_distance.dot(flat_normal);
-            let original_speed = vel.length();
-            let slide_speed = slide_velocity.length();
-            let slide_velocity = if slide_speed > EPSILON {
-                slide_velocity * (original_speed / slide_speed).min(1.0)
-            } else {
-                Vec3::ZERO
-            };
-            
-            // Quake 3–style velocity clipping
-            let collision_normal = if hit_count > 0 {
-                (avg_normal / hit_count as f32).normalize_or_zero()
-            } else {
-                direction.normalize_or_zero()
-            };
-            let speed = velocity.0.dot(collision_normal);
-            if speed < 0.0 {
-                velocity.0 -= speed * collision_normal;
-            }
-            
-            vel = slide_velocity;
-            if flat_normal.y > 0.7 && velocity.0.y < 0.0 {
-                grounded.0 = true;
-            }
-            iterations += 1;
-        } else {
-            position.0 += vel;
-            break;
+    #[test]
+    fn players_in_an_app_move_independently() {
+        let mut app = movement_app(MovementBackend::Transform);
+        let directions = [Vec2::Y, Vec2::X, Vec2::NEG_Y, Vec2::ZERO];
+        let players: Vec<Entity> = directions
+            .iter()
+            .enumerate()
+            .map(|(index, &direction)| spawn_player(&mut app, Vec3::X * (index as f32 * 5.0), direction))
+            .collect();
+        for _ in 0..33 {
+            app.update();
         }
+        let [forward, right, back, idle] = [0, 1, 2, 3].map(|index| {
+            let velocity = app.world().get::<CharacterVelocity>(players[index]).unwrap().0;
+            Vec3::new(velocity.x, 0.0, velocity.z)
+        });
+        assert!(forward.z < -1e-3 && forward.x.abs() < 1e-4, "forward: {forward}");
+        assert!(right.x > 1e-3 && right.z.abs() < 1e-4, "right: {right}");
+        assert!(back.z > 1e-3 && back.x.abs() < 1e-4, "back: {back}");
+        assert_eq!(idle, Vec3::ZERO);
+        // All fall the same way, since only their inputs differ
+        let falls: Vec<f32> =
+            players.iter().map(|&player| app.world().get::<CharacterVelocity>(player).unwrap().0.y).collect();
+        assert!(falls.iter().all(|&fall| fall == falls[0] && fall < 0.0), "falls: {falls:?}");
     }
-    
-    // Clamp Y for safety
-    if position.0.y < -100.0 { position.0.y = -100.0; }
-    if position.0.y > 1000.0 { position.0.y = 1000.0; }
-    
-    assert!(position.0.is_finite(), "Player position is not finite after movement: {:?}", position.0);
-    assert!(velocity.0.is_finite(), "Player velocity is not finite after movement: {:?}", velocity.0);
-    
-    if grounded.0 {
-        velocity.0.y = 0.0;
-        let ground_y = (position.0.y / 0.01).round() * 0.01;
-        if (position.0.y - ground_y).abs() < 0.02 {
-            position.0.y = ground_y;
+
+    /// Runs `movement` and `kinematic_movement` for a player walking forward through `backend`, and returns the
+    /// trajectory read back through the backend and the final `Transform` and `Position`.
+    fn simulate(backend: MovementBackend) -> (Vec<Vec3>, Transform, Position) {
+        let mut app = movement_app(backend);
+        let player = spawn_player(&mut app, Vec3::new(0.0, 3.0, 0.0), Vec2::Y);
+        let mut trajectory = Vec::new();
+        for _ in 0..32 {
+            app.update();
+            let world = app.world();
+            trajectory.push(backend.translation(world.get::<Transform>(player).unwrap(), world.get::<Position>(player)));
         }
+        let world = app.world();
+        (trajectory, *world.get::<Transform>(player).unwrap(), *world.get::<Position>(player).unwrap())
+    }
+
+    #[test]
+    fn backends_follow_the_same_trajectory() {
+        let (transform_trajectory, _, untouched_position) = simulate(MovementBackend::Transform);
+        let (position_trajectory, untouched_transform, _) = simulate(MovementBackend::Position);
+        assert_eq!(transform_trajectory, position_trajectory);
+        // Each backend only writes its own component
+        assert_eq!(untouched_position.0, Vec3::new(0.0, 3.0, 0.0));
+        assert_eq!(untouched_transform.translation, Vec3::new(0.0, 3.0, 0.0));
+        // The systems actually moved the player: falling and drifting forward
+        let end = transform_trajectory[31];
+        assert!(end.y < 2.5 && end.z < 0.0, "moved to {end}");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn position_backend_falls_back_to_transform() {
+        let mut transform = Transform::from_xyz(1.0, 2.0, 3.0);
+        assert_eq!(MovementBackend::Position.translation(&transform, None), transform.translation);
+        MovementBackend::Position.write_translation(Vec3::ONE, &mut transform, None);
+        assert_eq!(transform.translation, Vec3::ONE);
+    }
+}
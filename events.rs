@@ -0,0 +1,116 @@
+//! Movement events for gameplay, audio, animation, effects and stats, and the optional fall damage built on them.
+//!
+//! Events are sent by the core movement systems from the per-tick reports of the pure simulation in `step`
+//! (`MovementState::landing`, `jumped`, `left_ground`, `stepped_up` and `wall_hit`). Payloads only hold plain
+//! values, so they can be serialized for replays and networking.
+use bevy::prelude::*;
+
+use crate::game::player::movement::core::{kinematic_movement, MovementConfig, MovementCorePlugin};
+pub use crate::game::player::movement::step::JumpKind;
+
+/// Sent by `movement` when a player jumps.
+#[derive(Event, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Jumped {
+    /// The player that jumped.
+    pub entity: Entity,
+    /// What the player jumped off.
+    pub kind: JumpKind,
+    /// Velocity (units/sec) right after the jump.
+    pub velocity: Vec3,
+}
+
+/// Sent by `kinematic_movement` when a player touches down after being airborne.
+#[derive(Event, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Landed {
+    /// The player that landed.
+    pub entity: Entity,
+    /// Downward speed (units/sec) just before touching the ground.
+    pub impact_speed: f32,
+    /// Seconds spent airborne before landing.
+    pub air_time: f32,
+}
+
+/// Sent by `kinematic_movement` when a player walks off the ground without jumping.
+#[derive(Event, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct LeftGround {
+    /// The player that left the ground.
+    pub entity: Entity,
+    /// Velocity (units/sec) when leaving the ground.
+    pub velocity: Vec3,
+}
+
+/// Sent by `kinematic_movement` when a player starts stepping up onto a step.
+#[derive(Event, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SteppedUp {
+    /// The player that stepped up.
+    pub entity: Entity,
+    /// Height of the step (meters).
+    pub height: f32,
+}
+
+/// Sent by `kinematic_movement` when a player runs into a wall after not touching one.
+#[derive(Event, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct HitWall {
+    /// The player that hit the wall.
+    pub entity: Entity,
+    /// Horizontal normal of the wall, pointing away from it.
+    pub normal: Vec3,
+    /// Speed (units/sec) into the wall at impact.
+    pub speed: f32,
+}
+
+/// Sent by `apply_fall_damage` for hard landings, for the health system to apply.
+#[derive(Event, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct FallDamage {
+    /// The damaged player.
+    pub entity: Entity,
+    /// Damage to apply.
+    pub amount: f32,
+}
+
+/// Registers movement events (through `MovementCorePlugin`), `FallDamage` and the fall damage system.
+pub struct MovementEventsPlugin;
+
+impl Plugin for MovementEventsPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<MovementCorePlugin>() {
+            app.add_plugins(MovementCorePlugin);
+        }
+        app.add_event::<FallDamage>()
+            .add_systems(FixedUpdate, apply_fall_damage.after(kinematic_movement));
+    }
+}
+
+/// Returns the fall damage for a landing at `impact_speed`.
+///
+/// No damage below `fall_damage_min_speed`. Above it, damage grows from `fall_damage_min` to `fall_damage_max` at
+/// `fall_damage_max_speed`, following `t^fall_damage_exponent` where `t` is how far between the two speeds the
+/// impact is.
+pub fn fall_damage(impact_speed: f32, config: &MovementConfig) -> f32 {
+    if impact_speed < config.fall_damage_min_speed {
+        return 0.0;
+    }
+    let range = (config.fall_damage_max_speed - config.fall_damage_min_speed).max(f32::EPSILON);
+    let t = ((impact_speed - config.fall_damage_min_speed) / range).clamp(0.0, 1.0);
+    config.fall_damage_min + (config.fall_damage_max - config.fall_damage_min) * t.powf(config.fall_damage_exponent)
+}
+
+/// Sends `FallDamage` for every `Landed` event hard enough to hurt, if `MovementConfig::fall_damage_enabled`.
+///
+/// Should be run in `FixedUpdate` after `kinematic_movement`.
+pub fn apply_fall_damage(
+    mut landings: EventReader<Landed>,
+    mut damage: EventWriter<FallDamage>,
+    config: Res<MovementConfig>,
+) {
+    if !config.fall_damage_enabled {
+        landings.clear();
+        return;
+    }
+    for landed in landings.read() {
+        let amount = fall_damage(landed.impact_speed, &config);
+        if amount > 0.0 {
+            damage.write(FallDamage { entity: landed.entity, amount });
+        }
+    }
+}
@@ -3,24 +3,68 @@
 //! This module defines components and systems for tracking jump and fall timing for player movement.
 use bevy::prelude::*;
 
+use crate::game::player::movement::step::{advance_timers, MovementState};
+
 /// Marker component for tracking the time since a player last jumped.
 ///
 /// Used to prevent double-jumping and to time jump-related effects.
-#[derive(Component, Default, Reflect, serde::Serialize, serde::Deserialize)]
+#[derive(Component, Debug, Clone, Reflect, serde::Serialize, serde::Deserialize)]
 #[reflect(Component)]
 pub struct JustJumped {
     /// Seconds since the last jump. Resets to 0.0 on jump.
     pub timer: f32,
+    /// Seconds since knockback or a jump pad launched the player upwards. Resets to 0.0 on launch.
+    pub since_launch: f32,
+    /// Seconds since the player last landed. Resets to 0.0 on landing; used for CPMA-style double jumps.
+    pub since_landing: f32,
+    /// Whether the last landing was from a jump rather than from walking off a ledge. Only those allow double jumps.
+    pub landed_from_jump: bool,
+}
+impl Default for JustJumped {
+    fn default() -> Self {
+        Self { timer: f32::MAX, since_launch: f32::MAX, since_landing: f32::MAX, landed_from_jump: false }
+    }
 }
 /// Component for tracking how long the player has been falling.
 ///
 /// Used for landing logic and to distinguish between short hops and long falls.
-#[derive(Component, Default, Reflect, serde::Serialize, serde::Deserialize)]
+#[derive(Component, Debug, Clone, Default, Reflect, serde::Serialize, serde::Deserialize)]
 #[reflect(Component)]
 pub struct FallTimer {
     /// Seconds spent falling (not grounded).
     pub timer: f32,
 }
+/// Component for tracking the jump input: when it was last registered and whether it was held last tick.
+///
+/// Used for jump buffering (a jump pressed shortly before landing still fires on touchdown) and for
+/// press-edge detection in the `JumpMode`s that don't auto-hop.
+#[derive(Component, Debug, Clone, Reflect, serde::Serialize, serde::Deserialize)]
+#[reflect(Component)]
+pub struct JumpInputTimer {
+    /// Seconds since the jump input was last registered (held, or pressed depending on `JumpMode`).
+    /// Set to f32::MAX once a jump consumes it.
+    pub timer: f32,
+    /// Whether jump was held on the previous tick.
+    pub held: bool,
+}
+impl Default for JumpInputTimer {
+    fn default() -> Self {
+        Self { timer: f32::MAX, held: false }
+    }
+}
+/// Component for tracking the crouch-slide state and its timers.
+///
+/// Used to limit slide duration and to keep players from chaining slides back to back.
+#[derive(Component, Debug, Clone, Default, Reflect, serde::Serialize, serde::Deserialize)]
+#[reflect(Component)]
+pub struct SlideTimer {
+    /// Whether the player is currently sliding.
+    pub sliding: bool,
+    /// Seconds since the current slide started.
+    pub timer: f32,
+    /// Seconds left before another slide can start. Counts down to 0.0.
+    pub cooldown: f32,
+}
 /// Component for storing the previous frame's fall timer value.
 ///
 /// Used to detect landing events and for effects that depend on fall duration.
@@ -33,22 +77,38 @@ pub struct PreviousFallTimer {
 
 /// Updates the jump and fall timers for all players.
 ///
-/// - Increments `JustJumped.timer` if less than f32::MAX.
+/// - Increments `JustJumped.timer` and `JustJumped.since_launch` if less than f32::MAX.
+/// - Resets `JustJumped.since_landing` on landing, otherwise increments it if less than f32::MAX.
+/// - Records in `JustJumped.landed_from_jump` whether a landing ended a jump.
 /// - Resets `FallTimer` if grounded, otherwise increments it.
+/// - Increments `JumpInputTimer.timer` if less than f32::MAX.
+/// - Advances `SlideTimer.timer` while sliding and counts `SlideTimer.cooldown` down to 0.0.
 ///
-/// Should be run in `FixedUpdate` before movement logic.
+/// Wraps `step::advance_timers`. Should be run in `FixedUpdate` before movement logic.
 pub fn update_jump_and_fall_timers(
-    mut query: Query<(&mut JustJumped, &mut FallTimer, &crate::game::player::movement::core::CharacterVelocity, &crate::game::player::movement::core::Grounded), With<crate::game::player::Player>>,
+    mut query: Query<(&mut JustJumped, &mut FallTimer, &crate::game::player::movement::core::CharacterVelocity, &crate::game::player::movement::core::Grounded, Option<&mut JumpInputTimer>, Option<&mut SlideTimer>), With<crate::game::player::Player>>,
     time: Res<Time>,
 ) {
-    for (mut just_jumped, mut fall_timer, _velocity, grounded) in query.iter_mut() {
-        if just_jumped.timer < f32::MAX {
-            just_jumped.timer += time.delta_secs();
+    for (mut just_jumped, mut fall_timer, velocity, grounded, jump_input, slide) in query.iter_mut() {
+        let mut state = MovementState::from_components(Vec3::ZERO, velocity, grounded, Some(&*just_jumped), Some(&*fall_timer));
+        state.jump_input_timer = jump_input.as_ref().map_or(f32::MAX, |j| j.timer);
+        if let Some(slide) = slide.as_ref() {
+            state.sliding = slide.sliding;
+            state.slide_timer = slide.timer;
+            state.slide_cooldown = slide.cooldown;
+        }
+        advance_timers(&mut state, time.delta_secs());
+        just_jumped.timer = state.just_jumped;
+        just_jumped.since_launch = state.launch_timer;
+        just_jumped.since_landing = state.landing_timer;
+        just_jumped.landed_from_jump = state.landed_from_jump;
+        fall_timer.timer = state.fall_timer;
+        if let Some(mut jump_input) = jump_input {
+            jump_input.timer = state.jump_input_timer;
         }
-        if grounded.0 {
-            fall_timer.timer = 0.0;
-        } else {
-            fall_timer.timer += time.delta_secs();
+        if let Some(mut slide) = slide {
+            slide.timer = state.slide_timer;
+            slide.cooldown = state.slide_cooldown;
         }
     }
 }
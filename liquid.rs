@@ -0,0 +1,195 @@
+//! Liquid volumes (water, slime, lava) and swimming state for the player.
+//!
+//! Liquids are sensor colliders. `update_liquid_contacts` records which liquid a player is in and how deep, the
+//! swimming itself (buoyancy, drag, swim-up, water jumps) happens in `step::apply_input` and `step::kinematic_step`.
+use avian3d::prelude::*;
+use bevy::prelude::*;
+
+use crate::game::player::movement::core::{movement, Crouching, MovementBackend, PlayerHull, PlayerShape};
+
+/// Seconds between damage ticks while in a harmful liquid.
+pub const LIQUID_DAMAGE_INTERVAL: f32 = 1.0;
+
+/// Kind of liquid, selecting its default damage.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect, serde::Serialize, serde::Deserialize)]
+pub enum LiquidKind {
+    #[default]
+    Water,
+    Slime,
+    Lava,
+}
+
+impl LiquidKind {
+    /// Quake's damage per `LIQUID_DAMAGE_INTERVAL` at feet level for this liquid.
+    pub fn default_damage(self) -> f32 {
+        match self {
+            LiquidKind::Water => 0.0,
+            LiquidKind::Slime => 4.0,
+            LiquidKind::Lava => 10.0,
+        }
+    }
+}
+
+/// How deep a player is in a liquid, as in Quake's `waterlevel`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Reflect, serde::Serialize, serde::Deserialize)]
+pub enum WaterLevel {
+    #[default]
+    None,
+    /// Feet in the liquid.
+    Feet,
+    /// In up to the waist; the player swims from here on.
+    Waist,
+    /// Fully submerged.
+    Eyes,
+}
+
+/// Marks a liquid volume. The top of the collider's AABB is the liquid surface.
+///
+/// The collider on this entity is made a sensor.
+#[derive(Component, Debug, Clone, Default, Reflect, serde::Serialize, serde::Deserialize)]
+#[reflect(Component)]
+#[require(Sensor, CollidingEntities)]
+pub struct Liquid {
+    /// Kind of liquid.
+    pub kind: LiquidKind,
+    /// Damage per `LIQUID_DAMAGE_INTERVAL`, multiplied by the water level (1 at feet, 3 submerged).
+    pub damage: f32,
+}
+
+impl Liquid {
+    /// Creates a liquid with the Quake default damage for its kind.
+    pub fn new(kind: LiquidKind) -> Self {
+        Self { kind, damage: kind.default_damage() }
+    }
+}
+
+/// Liquid state of a player, updated by `update_liquid_contacts`.
+#[derive(Component, Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct LiquidContact {
+    /// The liquid volume the player is in, if any.
+    #[serde(skip)]
+    pub liquid: Option<Entity>,
+    /// Kind of that liquid.
+    pub kind: LiquidKind,
+    /// World height of the liquid surface.
+    pub surface: f32,
+    /// How deep the player is.
+    pub level: WaterLevel,
+    /// Seconds until the next damage tick.
+    pub damage_timer: f32,
+}
+
+/// Sent every `LIQUID_DAMAGE_INTERVAL` while a player is in a harmful liquid, for the health system to apply.
+#[derive(Event, Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct LiquidDamage {
+    /// The damaged player.
+    pub entity: Entity,
+    /// Kind of liquid dealing the damage.
+    pub kind: LiquidKind,
+    /// Damage to apply.
+    pub amount: f32,
+}
+
+/// Registers liquid components, events and systems before the core movement systems.
+pub struct LiquidPlugin;
+
+impl Plugin for LiquidPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<LiquidDamage>()
+            .register_type::<Liquid>()
+            .add_systems(FixedUpdate, (update_liquid_contacts, apply_liquid_damage).chain().before(movement));
+    }
+}
+
+/// Returns how deep a player with its body center at `translation` is in a liquid whose surface is at `surface`.
+///
+/// Feet, waist and eyes are measured from the bottom of the step region, like the ground cast.
+pub fn water_level(translation: Vec3, surface: f32, crouching: bool, hull: &PlayerHull) -> WaterLevel {
+    let feet = hull.feet(translation);
+    let height = if crouching { hull.crouch_height } else { hull.height };
+    if surface >= feet + height * 0.9 {
+        WaterLevel::Eyes
+    } else if surface >= feet + height * 0.5 {
+        WaterLevel::Waist
+    } else if surface > feet {
+        WaterLevel::Feet
+    } else {
+        WaterLevel::None
+    }
+}
+
+/// Records the liquid every player is in and their water level in `LiquidContact`.
+///
+/// - Uses the highest surface when a player overlaps several liquids.
+/// - Entering a harmful liquid schedules a damage tick right away.
+///
+/// Should be run in `FixedUpdate` before `movement`.
+pub fn update_liquid_contacts(
+    mut commands: Commands,
+    liquids: Query<(Entity, &Liquid, &CollidingEntities, &ColliderAabb)>,
+    colliders: Query<&ColliderOf>,
+    mut players: Query<
+        (Entity, &Transform, Option<&Position>, Option<&Crouching>, Option<&mut LiquidContact>),
+        With<crate::game::player::Player>,
+    >,
+    backend: Option<Res<MovementBackend>>,
+    shape: Option<Res<PlayerShape>>,
+) {
+    let backend = backend.map_or_else(MovementBackend::default, |b| *b);
+    let hull = shape.map_or_else(PlayerShape::default, |s| *s).hull();
+    let mut touching: std::collections::HashMap<Entity, (Entity, LiquidKind, f32)> = std::collections::HashMap::new();
+    for (liquid_entity, liquid, colliding, aabb) in liquids.iter() {
+        for &collider in colliding.iter() {
+            let body = colliders.get(collider).map_or(collider, |collider_of| collider_of.body);
+            let surface = aabb.max.y;
+            if touching.get(&body).is_none_or(|&(_, _, other)| surface > other) {
+                touching.insert(body, (liquid_entity, liquid.kind, surface));
+            }
+        }
+    }
+    for (entity, transform, position, crouching, contact) in players.iter_mut() {
+        let translation = backend.translation(transform, position);
+        let crouching = crouching.is_some_and(|c| c.0);
+        let new_contact = match touching.get(&entity) {
+            Some(&(liquid, kind, surface)) => LiquidContact {
+                liquid: Some(liquid),
+                kind,
+                surface,
+                level: water_level(translation, surface, crouching, &hull),
+                damage_timer: 0.0,
+            },
+            None => LiquidContact::default(),
+        };
+        if let Some(mut contact) = contact {
+            let entered = contact.liquid.is_none() && new_contact.liquid.is_some();
+            let damage_timer = if entered { 0.0 } else { contact.damage_timer };
+            *contact = LiquidContact { damage_timer, ..new_contact };
+        } else if new_contact.liquid.is_some() {
+            commands.entity(entity).insert(new_contact);
+        }
+    }
+}
+
+/// Sends `LiquidDamage` for players in harmful liquids every `LIQUID_DAMAGE_INTERVAL`.
+///
+/// Should be run in `FixedUpdate` after `update_liquid_contacts`.
+pub fn apply_liquid_damage(
+    mut players: Query<(Entity, &mut LiquidContact), With<crate::game::player::Player>>,
+    liquids: Query<&Liquid>,
+    mut damage: EventWriter<LiquidDamage>,
+    time: Res<Time>,
+) {
+    for (entity, mut contact) in players.iter_mut() {
+        let Some(liquid) = contact.liquid.and_then(|liquid| liquids.get(liquid).ok()) else {
+            continue;
+        };
+        if liquid.damage <= 0.0 || contact.level == WaterLevel::None {
+            continue;
+        }
+        contact.damage_timer -= time.delta_secs();
+        if contact.damage_timer <= 0.0 {
+            contact.damage_timer += LIQUID_DAMAGE_INTERVAL;
+            damage.write(LiquidDamage { entity, kind: liquid.kind, amount: liquid.damage * contact.level as u8 as f32 });
+        }
+    }
+}
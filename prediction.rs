@@ -0,0 +1,392 @@
+//! Client-side prediction and server reconciliation for the kinematic player controller.
+//!
+//! The locally controlled player is simulated ahead of the server. Every fixed tick the applied input and the
+//! resulting movement state are recorded under an input sequence number. When an authoritative snapshot arrives,
+//! the matching history entry is compared against it; on mismatch the player is rewound to the snapshot and every
+//! unacknowledged input is re-simulated. The resulting visual jump is smoothed out over a few frames.
+use std::collections::VecDeque;
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+
+use crate::game::player::input::NetworkedPlayerInput;
+use crate::game::player::movement::core::{
+    kinematic_movement, movement, CharacterVelocity, Crouching, ExtraMovementInput, GroundInfo, Grounded,
+    MovementBackend, MovementConfig, PlayerShape,
+};
+use crate::game::player::movement::jump::{
+    update_jump_and_fall_timers, FallTimer, JumpInputTimer, JustJumped, SlideTimer,
+};
+use crate::game::player::movement::liquid::LiquidContact;
+use crate::game::player::movement::step::{
+    step, CollisionWorld, MovementInput, MovementState, OptionalMovementComponents, SpatialCollisionWorld,
+};
+use crate::game::player::movement::triggers::LadderContact;
+use crate::game::player::movement::wall::{WallContact, WallRunTimer};
+
+/// Maximum number of unacknowledged ticks kept per player (2 seconds at 64 Hz).
+pub const PREDICTION_HISTORY_LEN: usize = 128;
+/// Position error (meters) above which a snapshot triggers a rollback.
+pub const POSITION_ERROR_TOLERANCE: f32 = 0.01;
+/// Velocity error (units/sec) above which a snapshot triggers a rollback.
+pub const VELOCITY_ERROR_TOLERANCE: f32 = 0.05;
+/// Visual errors larger than this (meters) are snapped instead of smoothed.
+pub const MAX_SMOOTHED_ERROR: f32 = 2.0;
+/// Exponential decay rate of the visual error offset (1/sec).
+pub const ERROR_SMOOTHING_RATE: f32 = 15.0;
+
+/// Marker component for players that are simulated locally ahead of the server.
+///
+/// Only the locally controlled player should carry this; remote players follow server state directly.
+#[derive(Component, Default, Reflect)]
+#[reflect(Component)]
+#[require(InputSequence, PredictionHistory, PredictionError)]
+pub struct Predicted;
+
+/// Sequence number of the input applied in the current fixed tick.
+///
+/// Advanced once per tick before `movement`. The networking layer stamps outgoing inputs with this value and the
+/// server echoes the last processed sequence back in `AuthoritativeSnapshot`.
+#[derive(Component, Debug, Clone, Copy, Default, Reflect, serde::Serialize, serde::Deserialize)]
+#[reflect(Component)]
+pub struct InputSequence(pub u32);
+
+/// Input and resulting movement state recorded for a single predicted tick.
+#[derive(Debug, Clone)]
+pub struct PredictedTick {
+    /// Input sequence number of this tick.
+    pub sequence: u32,
+    /// Delta time the tick was simulated with (seconds).
+    pub dt: f32,
+    /// Input applied during this tick.
+    pub input: MovementInput,
+    /// Translation, velocity, grounded state and jump/fall timers after the tick.
+    pub state: MovementState,
+}
+
+/// History of predicted ticks not yet acknowledged by the server, oldest first.
+#[derive(Component, Debug, Default)]
+pub struct PredictionHistory {
+    /// Predicted ticks in the order they were simulated, oldest first. Ticks up to `last_acknowledged` are dropped
+    /// by `acknowledge`, so only inputs the server has not processed yet remain to be replayed.
+    pub ticks: VecDeque<PredictedTick>,
+    /// Newest sequence acknowledged by the server, used to drop late or reordered snapshots.
+    pub last_acknowledged: Option<u32>,
+}
+
+impl PredictionHistory {
+    /// Records a tick, dropping the oldest one once the history is full.
+    pub fn push(&mut self, tick: PredictedTick) {
+        if self.ticks.len() >= PREDICTION_HISTORY_LEN {
+            self.ticks.pop_front();
+        }
+        self.ticks.push_back(tick);
+    }
+
+    /// Returns the recorded tick with the given sequence number, if it is still in the history.
+    pub fn get(&self, sequence: u32) -> Option<&PredictedTick> {
+        self.ticks.iter().find(|tick| tick.sequence == sequence)
+    }
+
+    /// Returns true if a snapshot for `sequence` is older than one already acknowledged.
+    pub fn is_stale(&self, sequence: u32) -> bool {
+        self.last_acknowledged.is_some_and(|last| !sequence_at_or_after(sequence, last))
+    }
+
+    /// Drops every tick up to and including `sequence` and remembers it as the newest acknowledged sequence.
+    pub fn acknowledge(&mut self, sequence: u32) {
+        self.last_acknowledged = Some(sequence);
+        while self.ticks.front().is_some_and(|tick| sequence_at_or_after(sequence, tick.sequence)) {
+            self.ticks.pop_front();
+        }
+    }
+}
+
+/// Remaining visual offset (world space) between the rendered and the simulated player after a correction.
+#[derive(Component, Debug, Default)]
+pub struct PredictionError {
+    pub offset: Vec3,
+}
+
+/// Marks a child entity (mesh, model) that is offset by `PredictionError` instead of snapping on corrections.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct PredictionVisual {
+    /// Local translation of the visual when there is no error to smooth.
+    pub base_translation: Vec3,
+}
+
+/// Authoritative movement state for a player, as simulated by the server.
+#[derive(Event, Debug, Clone)]
+pub struct AuthoritativeSnapshot {
+    /// Local entity the snapshot applies to.
+    pub entity: Entity,
+    /// Last input sequence the server processed for this player.
+    pub sequence: u32,
+    /// Server movement state after processing `sequence`.
+    pub state: MovementState,
+}
+
+/// Registers prediction events and systems around the core movement systems.
+pub struct PredictionPlugin;
+
+impl Plugin for PredictionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AuthoritativeSnapshot>()
+            .register_type::<Predicted>()
+            .register_type::<InputSequence>()
+            .register_type::<PredictionVisual>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    reconcile_predicted_players.before(update_jump_and_fall_timers),
+                    advance_input_sequence.before(movement),
+                    record_prediction_history.after(kinematic_movement),
+                ),
+            )
+            .add_systems(Update, smooth_prediction_error);
+    }
+}
+
+/// Advances the input sequence of every predicted player.
+///
+/// Should be run in `FixedUpdate` before `movement`.
+pub fn advance_input_sequence(
+    mut query: Query<&mut InputSequence, (With<Predicted>, With<crate::game::player::Player>)>,
+) {
+    for mut sequence in query.iter_mut() {
+        sequence.0 = sequence.0.wrapping_add(1);
+    }
+}
+
+/// Records the input and resulting movement state of every predicted player.
+///
+/// Should be run in `FixedUpdate` after `kinematic_movement`.
+pub fn record_prediction_history(
+    mut query: Query<
+        (
+            &InputSequence,
+            &NetworkedPlayerInput,
+            &Transform,
+            &CharacterVelocity,
+            &Grounded,
+            &JustJumped,
+            &FallTimer,
+            &mut PredictionHistory,
+            Option<&Position>,
+            Option<&Crouching>,
+            Option<&JumpInputTimer>,
+            Option<&GroundInfo>,
+            Option<&SlideTimer>,
+            (Option<&WallContact>, Option<&WallRunTimer>, Option<&LadderContact>, Option<&LiquidContact>),
+            Option<&ExtraMovementInput>,
+        ),
+        (With<Predicted>, With<crate::game::player::Player>),
+    >,
+    time: Res<Time>,
+    backend: Option<Res<MovementBackend>>,
+    shape: Option<Res<PlayerShape>>,
+) {
+    let backend = backend.map_or_else(MovementBackend::default, |b| *b);
+    let shape = shape.map_or_else(PlayerShape::default, |s| *s);
+    for (sequence, input, transform, velocity, grounded, just_jumped, fall_timer, mut history, position, crouching, jump_input, ground_info, slide, (wall_contact, wall_run, ladder, liquid), extra_input) in query.iter_mut() {
+        let translation = backend.translation(transform, position);
+        let mut state = MovementState::from_components(translation, velocity, grounded, Some(just_jumped), Some(fall_timer));
+        state.load_optional_components(OptionalMovementComponents {
+            crouching,
+            jump_input,
+            ground_info,
+            slide,
+            wall_contact,
+            wall_run,
+            ladder,
+            liquid,
+        });
+        state.shape = shape;
+        history.push(PredictedTick {
+            sequence: sequence.0,
+            dt: time.delta_secs(),
+            input: MovementInput::from_components(input, extra_input),
+            state,
+        });
+    }
+}
+
+/// Applies authoritative snapshots to predicted players, rewinding and re-simulating on mismatch.
+///
+/// - Snapshots older than the newest acknowledged one (late or out of order) are ignored.
+/// - Snapshots matching the recorded prediction only trim the history.
+/// - Otherwise the player is reset to the snapshot and every pending input is replayed by `replay_ticks` through
+///   `step::step`, the same simulation the movement systems wrap.
+/// - The difference between the old and the corrected position is handed to `PredictionError` for smoothing.
+///
+/// Should be run in `FixedUpdate` before `update_jump_and_fall_timers`.
+pub fn reconcile_predicted_players(
+    mut snapshots: EventReader<AuthoritativeSnapshot>,
+    mut query: Query<
+        (
+            &mut Transform,
+            &mut CharacterVelocity,
+            &Children,
+            &mut Grounded,
+            &mut JustJumped,
+            &mut FallTimer,
+            &mut PredictionHistory,
+            &mut PredictionError,
+            Option<&mut Position>,
+            Option<&mut Crouching>,
+            Option<&mut JumpInputTimer>,
+            Option<&mut SlideTimer>,
+            (Option<&mut WallContact>, Option<&mut WallRunTimer>),
+        ),
+        (With<Predicted>, With<crate::game::player::Player>),
+    >,
+    spatial_query: SpatialQuery,
+    config: Res<MovementConfig>,
+    backend: Option<Res<MovementBackend>>,
+    shape: Option<Res<PlayerShape>>,
+) {
+    let backend = backend.map_or_else(MovementBackend::default, |b| *b);
+    let shape = shape.map_or_else(PlayerShape::default, |s| *s);
+    for snapshot in snapshots.read() {
+        let Ok((mut transform, mut velocity, children, mut grounded, mut just_jumped, mut fall_timer, mut history, mut error, mut position, crouching, jump_input, slide, (wall_contact, wall_run))) =
+            query.get_mut(snapshot.entity)
+        else {
+            continue;
+        };
+        // Late or reordered snapshots would rewind the player past inputs a newer snapshot already covered
+        if history.is_stale(snapshot.sequence) {
+            continue;
+        }
+        let needs_rollback = history
+            .get(snapshot.sequence)
+            .map_or(true, |predicted| !matches_snapshot(&predicted.state, &snapshot.state));
+        history.acknowledge(snapshot.sequence);
+        if !needs_rollback {
+            continue;
+        }
+
+        // Replay every input the server has not processed yet, refreshing the recorded states as we go
+        let world = SpatialCollisionWorld::new(&spatial_query, std::iter::once(snapshot.entity).chain(children.iter()));
+        let mut state = snapshot.state;
+        state.shape = shape;
+        let state = replay_ticks(state, &mut history.ticks, &config, &world);
+
+        error.offset += backend.translation(&transform, position.as_deref()) - state.translation;
+        if error.offset.length() > MAX_SMOOTHED_ERROR {
+            error.offset = Vec3::ZERO;
+        }
+        backend.write_translation(state.translation, &mut transform, position.as_deref_mut());
+        state.write_components(&mut velocity, &mut grounded, Some(&mut *just_jumped), Some(&mut *fall_timer));
+        if let Some(mut crouching) = crouching {
+            crouching.0 = state.crouching;
+        }
+        if let Some(mut jump_input) = jump_input {
+            jump_input.timer = state.jump_input_timer;
+            jump_input.held = state.jump_held;
+        }
+        if let Some(mut slide) = slide {
+            slide.sliding = state.sliding;
+            slide.timer = state.slide_timer;
+            slide.cooldown = state.slide_cooldown;
+        }
+        if let Some(mut wall_contact) = wall_contact {
+            wall_contact.normal = state.wall_normal;
+            wall_contact.entity = state.wall_entity;
+            wall_contact.timer = state.wall_timer;
+        }
+        if let Some(mut wall_run) = wall_run {
+            wall_run.running = state.wall_running;
+            wall_run.timer = state.wall_run_timer;
+        }
+    }
+}
+
+/// Re-simulates `ticks` starting from `state`, overwriting each recorded state. Returns the final state.
+///
+/// Ladder, liquid and platform contacts come from trigger and platform systems that are not re-run, so they are
+/// restored from each tick's recorded state before its step, as the movement systems loaded them at the time.
+pub fn replay_ticks(
+    mut state: MovementState,
+    ticks: &mut VecDeque<PredictedTick>,
+    config: &MovementConfig,
+    world: &dyn CollisionWorld,
+) -> MovementState {
+    for tick in ticks.iter_mut() {
+        state.on_ladder = tick.state.on_ladder;
+        state.ladder_normal = tick.state.ladder_normal;
+        state.liquid_surface = tick.state.liquid_surface;
+        state.platform_velocity = tick.state.platform_velocity;
+        state = step(state, &tick.input, config, tick.dt, world);
+        tick.state = state;
+    }
+    state
+}
+
+/// Decays the visual prediction error and offsets `PredictionVisual` children by the remainder.
+///
+/// Should be run in `Update`.
+pub fn smooth_prediction_error(
+    mut players: Query<(&mut PredictionError, &GlobalTransform, &Children), With<Predicted>>,
+    mut visuals: Query<(&PredictionVisual, &mut Transform)>,
+    time: Res<Time>,
+) {
+    for (mut error, global_transform, children) in players.iter_mut() {
+        error.offset *= (-ERROR_SMOOTHING_RATE * time.delta_secs()).exp();
+        if error.offset.length_squared() < 1e-8 {
+            error.offset = Vec3::ZERO;
+        }
+        let local_offset = global_transform.rotation().inverse() * error.offset;
+        for child in children.iter() {
+            if let Ok((visual, mut transform)) = visuals.get_mut(child) {
+                transform.translation = visual.base_translation + local_offset;
+            }
+        }
+    }
+}
+
+/// Returns true if the predicted state agrees with the authoritative one within tolerance.
+fn matches_snapshot(predicted: &MovementState, authoritative: &MovementState) -> bool {
+    predicted.translation.distance(authoritative.translation) <= POSITION_ERROR_TOLERANCE
+        && predicted.velocity.distance(authoritative.velocity) <= VELOCITY_ERROR_TOLERANCE
+        && predicted.grounded == authoritative.grounded
+}
+
+/// Returns true if sequence `a` is the same as or newer than `b`, accounting for wrap-around.
+fn sequence_at_or_after(a: u32, b: u32) -> bool {
+    (a.wrapping_sub(b) as i32) >= 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::player::movement::step::test_world::PlaneWorld;
+
+    const DT: f32 = 1.0 / 64.0;
+
+    #[test]
+    fn replay_crosses_water_surface_like_the_prediction() {
+        let world = PlaneWorld::new([]);
+        let config = MovementConfig::default();
+        let surface = 0.0;
+        let input = MovementInput::default();
+        let mut state = MovementState { translation: Vec3::Y * 2.0, shape: PlayerShape::Aabb, ..default() };
+        let mut history = PredictionHistory::default();
+        for sequence in 0..64 {
+            // The liquid volume flags the player once it reaches the water, as `update_liquid_contacts` would
+            state.liquid_surface = (state.translation.y < surface + 1.0).then_some(surface);
+            state = step(state, &input, &config, DT, &world);
+            history.push(PredictedTick { sequence, dt: DT, input, state });
+        }
+        let predicted = state;
+        assert!(history.get(1).unwrap().state.liquid_surface.is_none());
+        assert_eq!(predicted.liquid_surface, Some(surface));
+
+        // The server agrees with the first tick; replaying the rest must enter the water on the same tick
+        let snapshot = history.get(0).unwrap().state;
+        history.acknowledge(0);
+        let replayed = replay_ticks(snapshot, &mut history.ticks, &config, &world);
+        assert_eq!(replayed.translation, predicted.translation);
+        assert_eq!(replayed.velocity, predicted.velocity);
+    }
+}
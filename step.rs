@@ -0,0 +1,1923 @@
+//! Pure, ECS-free player movement simulation.
+//!
+//! `step` advances a `MovementState` by one tick from a `MovementInput`, the `MovementConfig`, a delta time and a
+//! `CollisionWorld`. The Bevy systems in `core` and `jump` wrap the individual stages of a step, while prediction,
+//! bots, headless servers and tests can call `step` directly without a Bevy `App`.
+use std::cell::RefCell;
+
+use avian3d::debug_render::{PhysicsGizmos, PhysicsGizmoExt};
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::game::player::movement::core::{
+    AirMovementModel, CharacterVelocity, Crouching, ExtraMovementInput, GroundInfo, JumpMode, Grounded,
+    MovementConfig, PlayerShape, StepMode, QUAKE_UNIT_SCALE,
+};
+use crate::game::player::movement::jump::{FallTimer, JumpInputTimer, JustJumped, SlideTimer};
+use crate::game::player::movement::liquid::{water_level, LiquidContact, WaterLevel};
+use crate::game::player::movement::triggers::LadderContact;
+use crate::game::player::movement::wall::{WallContact, WallRunTimer};
+
+/// Movement state of a single player, decoupled from its ECS components.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MovementState {
+    /// Collider center in world space.
+    pub translation: Vec3,
+    /// Velocity in world space (units/sec).
+    pub velocity: Vec3,
+    /// Whether the player is on the ground.
+    pub grounded: bool,
+    /// Seconds since the last jump (`f32::MAX` if the player never jumped).
+    pub just_jumped: f32,
+    /// Seconds since an upward impulse or jump pad launched the player (`f32::MAX` if never).
+    ///
+    /// Kept apart from `just_jumped` so launches skip ground snapping and coyote time without counting as jumps.
+    pub launch_timer: f32,
+    /// Seconds since the player last landed (`f32::MAX` if the player never landed).
+    pub landing_timer: f32,
+    /// Whether the last landing ended a fall that started with (or included) a jump, for double jumps.
+    pub landed_from_jump: bool,
+    /// Seconds spent airborne.
+    pub fall_timer: f32,
+    /// Whether the player is crouching (shorter body, reduced ground speed).
+    pub crouching: bool,
+    /// Seconds since jump input was last registered (`f32::MAX` once consumed), for jump buffering.
+    pub jump_input_timer: f32,
+    /// Whether jump was held on the previous tick, for press-edge detection.
+    pub jump_held: bool,
+    /// Normal of the ground below the player from the last ground cast (`Vec3::ZERO` if none in range).
+    pub ground_normal: Vec3,
+    /// Entity hit by the last ground cast, if any.
+    #[serde(skip)]
+    pub ground_entity: Option<Entity>,
+    /// Velocity of the ground platform under the player, at the player's position (`Vec3::ZERO` for static ground).
+    ///
+    /// Provided by the caller from the previous tick's `ground_entity`, since the simulation has no access to bodies.
+    pub platform_velocity: Vec3,
+    /// Whether the player is crouch-sliding.
+    pub sliding: bool,
+    /// Seconds since the current slide started.
+    pub slide_timer: f32,
+    /// Seconds left before another slide can start.
+    pub slide_cooldown: f32,
+    /// Horizontal normal of the last wall the player touched.
+    pub wall_normal: Vec3,
+    /// Entity of the last wall the player touched, if any.
+    #[serde(skip)]
+    pub wall_entity: Option<Entity>,
+    /// Seconds since the player last touched a wall (`f32::MAX` if never, or once a wall jump used the contact).
+    pub wall_timer: f32,
+    /// Whether the player is wall running.
+    pub wall_running: bool,
+    /// Seconds spent wall running since the player was last grounded.
+    pub wall_run_timer: f32,
+    /// Whether the player is inside a ladder volume. Provided by the caller.
+    pub on_ladder: bool,
+    /// Direction pointing away from the ladder's climbing surface (`Vec3::ZERO` if unknown).
+    pub ladder_normal: Vec3,
+    /// World height of the surface of the liquid the player is in, if any. Provided by the caller.
+    pub liquid_surface: Option<f32>,
+    /// Height (meters) still to be climbed by step smoothing after the last tick, 0.0 if not stepping.
+    pub step_offset: f32,
+    /// Shape of the player body for all casts. Provided by the caller from the `PlayerShape` resource.
+    pub shape: PlayerShape,
+    /// Set by `kinematic_step` on the tick the player lands, `None` otherwise.
+    pub landing: Option<Landing>,
+    /// Set by `apply_input` and `check_water_jump` on the tick the player jumps, `None` otherwise.
+    pub jumped: Option<JumpKind>,
+    /// Set by `kinematic_step` on the tick the player walks off the ground without jumping.
+    pub left_ground: bool,
+    /// Set by `kinematic_step` to the step height on the tick the player starts stepping up, `None` otherwise.
+    pub stepped_up: Option<f32>,
+    /// Set by `kinematic_step` on the tick the player runs into a wall after not touching one, `None` otherwise.
+    pub wall_hit: Option<WallHit>,
+}
+
+/// A player's optional movement components, loaded into a `MovementState` by `load_optional_components`.
+///
+/// Components a system does not query can be left as `None`, which loads them as missing.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OptionalMovementComponents<'a> {
+    /// Loads `crouching`.
+    pub crouching: Option<&'a Crouching>,
+    /// Loads `jump_input_timer` and `jump_held`.
+    pub jump_input: Option<&'a JumpInputTimer>,
+    /// Loads `ground_normal`, `ground_entity`, `platform_velocity` and `step_offset` from the previous tick.
+    pub ground_info: Option<&'a GroundInfo>,
+    /// Loads `sliding`, `slide_timer` and `slide_cooldown`.
+    pub slide: Option<&'a SlideTimer>,
+    /// Loads `wall_normal`, `wall_entity` and `wall_timer`.
+    pub wall_contact: Option<&'a WallContact>,
+    /// Loads `wall_running` and `wall_run_timer`.
+    pub wall_run: Option<&'a WallRunTimer>,
+    /// Loads `on_ladder` and `ladder_normal`.
+    pub ladder: Option<&'a LadderContact>,
+    /// Loads `liquid_surface`.
+    pub liquid: Option<&'a LiquidContact>,
+}
+
+/// What the player jumped off.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JumpKind {
+    /// Regular jump from the ground (including coyote-time jumps).
+    #[default]
+    Ground,
+    /// Wall jump.
+    Wall,
+    /// Dismount jump off a ladder.
+    Ladder,
+    /// Jump out of a liquid onto a ledge.
+    Water,
+}
+
+/// Details of a wall impact, reported by `kinematic_step`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct WallHit {
+    /// Horizontal normal of the wall, pointing away from it.
+    pub normal: Vec3,
+    /// Speed (units/sec) into the wall at impact.
+    pub speed: f32,
+    /// Entity of the wall's collider.
+    #[serde(skip)]
+    pub entity: Option<Entity>,
+}
+
+/// Details of a landing, reported by `kinematic_step`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Landing {
+    /// Downward speed (units/sec) just before touching the ground.
+    pub impact_speed: f32,
+    /// Seconds spent airborne before landing.
+    pub air_time: f32,
+}
+
+impl Default for MovementState {
+    /// A player at rest at the origin that never jumped, landed, pressed jump or touched a wall.
+    fn default() -> Self {
+        Self {
+            translation: Vec3::ZERO,
+            velocity: Vec3::ZERO,
+            grounded: false,
+            just_jumped: f32::MAX,
+            launch_timer: f32::MAX,
+            landing_timer: f32::MAX,
+            landed_from_jump: false,
+            fall_timer: 0.0,
+            crouching: false,
+            jump_input_timer: f32::MAX,
+            jump_held: false,
+            ground_normal: Vec3::ZERO,
+            ground_entity: None,
+            platform_velocity: Vec3::ZERO,
+            sliding: false,
+            slide_timer: 0.0,
+            slide_cooldown: 0.0,
+            wall_normal: Vec3::ZERO,
+            wall_entity: None,
+            wall_timer: f32::MAX,
+            wall_running: false,
+            wall_run_timer: 0.0,
+            on_ladder: false,
+            ladder_normal: Vec3::ZERO,
+            liquid_surface: None,
+            step_offset: 0.0,
+            shape: PlayerShape::Cylinder,
+            landing: None,
+            jumped: None,
+            left_ground: false,
+            stepped_up: None,
+            wall_hit: None,
+        }
+    }
+}
+
+impl MovementState {
+    /// Builds a state from a player's movement components.
+    ///
+    /// Missing timers behave as they do in the systems: no recent jump and no air time.
+    pub fn from_components(
+        translation: Vec3,
+        velocity: &CharacterVelocity,
+        grounded: &Grounded,
+        just_jumped: Option<&JustJumped>,
+        fall_timer: Option<&FallTimer>,
+    ) -> Self {
+        Self {
+            translation,
+            velocity: velocity.0,
+            grounded: grounded.0,
+            just_jumped: just_jumped.map_or(f32::MAX, |j| j.timer),
+            launch_timer: just_jumped.map_or(f32::MAX, |j| j.since_launch),
+            landing_timer: just_jumped.map_or(f32::MAX, |j| j.since_landing),
+            landed_from_jump: just_jumped.is_some_and(|j| j.landed_from_jump),
+            fall_timer: fall_timer.map_or(0.0, |f| f.timer),
+            ..Self::default()
+        }
+    }
+
+    /// Loads crouching, jump input, ground, slide, wall, ladder and liquid state from a player's optional components.
+    ///
+    /// Missing components load as their defaults: not crouching, no buffered jump, no ground, wall or liquid.
+    pub fn load_optional_components(&mut self, components: OptionalMovementComponents) {
+        let OptionalMovementComponents { crouching, jump_input, ground_info, slide, wall_contact, wall_run, ladder, liquid } =
+            components;
+        self.crouching = crouching.is_some_and(|c| c.0);
+        self.jump_input_timer = jump_input.map_or(f32::MAX, |j| j.timer);
+        self.jump_held = jump_input.is_some_and(|j| j.held);
+        self.ground_normal = ground_info.map_or(Vec3::ZERO, |g| g.normal);
+        self.ground_entity = ground_info.and_then(|g| g.entity);
+        self.platform_velocity = ground_info.map_or(Vec3::ZERO, |g| g.platform_velocity);
+        self.step_offset = ground_info.map_or(0.0, |g| g.step_offset);
+        self.sliding = slide.is_some_and(|s| s.sliding);
+        self.slide_timer = slide.map_or(0.0, |s| s.timer);
+        self.slide_cooldown = slide.map_or(0.0, |s| s.cooldown);
+        self.wall_normal = wall_contact.map_or(Vec3::ZERO, |w| w.normal);
+        self.wall_entity = wall_contact.and_then(|w| w.entity);
+        self.wall_timer = wall_contact.map_or(f32::MAX, |w| w.timer);
+        self.wall_running = wall_run.is_some_and(|w| w.running);
+        self.wall_run_timer = wall_run.map_or(0.0, |w| w.timer);
+        self.on_ladder = ladder.is_some_and(|l| l.on_ladder);
+        self.ladder_normal = ladder.map_or(Vec3::ZERO, |l| l.normal);
+        self.liquid_surface = liquid.and_then(|l| l.liquid.map(|_| l.surface));
+    }
+
+    /// Clears the per-tick event reports (`landing`, `jumped`, `left_ground`, `stepped_up` and `wall_hit`).
+    pub fn clear_events(&mut self) {
+        self.landing = None;
+        self.jumped = None;
+        self.left_ground = false;
+        self.stepped_up = None;
+        self.wall_hit = None;
+    }
+
+    /// Writes velocity, grounded state and any provided timers back to a player's components.
+    ///
+    /// The translation is left to the caller, since it may live in `Transform` or `Position`.
+    pub fn write_components(
+        &self,
+        velocity: &mut CharacterVelocity,
+        grounded: &mut Grounded,
+        just_jumped: Option<&mut JustJumped>,
+        fall_timer: Option<&mut FallTimer>,
+    ) {
+        velocity.0 = self.velocity;
+        grounded.0 = self.grounded;
+        if let Some(jj) = just_jumped {
+            jj.timer = self.just_jumped;
+            jj.since_launch = self.launch_timer;
+            jj.since_landing = self.landing_timer;
+            jj.landed_from_jump = self.landed_from_jump;
+        }
+        if let Some(ft) = fall_timer {
+            ft.timer = self.fall_timer;
+        }
+    }
+}
+
+/// Player input for a single movement step.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct MovementInput {
+    /// Local movement direction (x = right, y = forward).
+    pub movement_direction: Vec2,
+    /// Analog movement strength in `[0, 1]`.
+    pub movement_magnitude: f32,
+    /// View yaw (radians) used to rotate the movement direction into world space.
+    pub yaw_rotation: f32,
+    /// View pitch (radians, positive looking up), used for climbing ladders.
+    pub pitch_rotation: f32,
+    /// Whether jump is held this tick.
+    pub jump_pressed: bool,
+    /// Whether crouch is held this tick.
+    pub crouch_pressed: bool,
+}
+
+impl MovementInput {
+    /// Builds the input for a step from a player's input components.
+    ///
+    /// A missing `ExtraMovementInput` means crouch is not held and the view is level.
+    pub fn from_components(
+        input: &crate::game::player::input::NetworkedPlayerInput,
+        extra: Option<&ExtraMovementInput>,
+    ) -> Self {
+        Self {
+            movement_direction: input.movement_direction,
+            movement_magnitude: input.movement_magnitude,
+            yaw_rotation: input.yaw_rotation,
+            pitch_rotation: extra.map_or(0.0, |e| e.pitch_rotation),
+            jump_pressed: input.jump_pressed,
+            crouch_pressed: extra.is_some_and(|e| e.crouch_pressed),
+        }
+    }
+}
+
+/// Collision queries needed by the movement simulation.
+///
+/// Implemented for Avian's `SpatialQuery` by `SpatialCollisionWorld`; tests and headless tools can provide their own.
+pub trait CollisionWorld {
+    /// Casts `shape` from `origin` along `direction`, returning the first hit within `max_distance`.
+    fn cast_shape(
+        &self,
+        shape: &Collider,
+        origin: Vec3,
+        rotation: Quat,
+        direction: Dir3,
+        max_distance: f32,
+    ) -> Option<ShapeHitData>;
+
+    /// Casts `shape` from `origin` along `direction`, returning up to `max_hits` hits within `max_distance`.
+    fn shape_hits(
+        &self,
+        shape: &Collider,
+        origin: Vec3,
+        rotation: Quat,
+        direction: Dir3,
+        max_distance: f32,
+        max_hits: u32,
+    ) -> Vec<ShapeHitData>;
+
+    /// Called for every cast the simulation wants visualised. Does nothing by default.
+    fn debug_shape_cast(&self, _shape: &Collider, _origin: Vec3, _direction: Dir3, _distance: f32, _ray_color: Color) {}
+}
+
+/// A shape cast recorded for debug rendering.
+struct DebugShapeCast {
+    shape: Collider,
+    origin: Vec3,
+    direction: Dir3,
+    distance: f32,
+    ray_color: Color,
+}
+
+/// `CollisionWorld` backed by Avian's `SpatialQuery`, excluding the simulated player's own entities.
+///
+/// Debug casts are buffered while debug shape casts are enabled and drawn with `draw_debug`.
+pub struct SpatialCollisionWorld<'a, 'w, 's> {
+    spatial_query: &'a SpatialQuery<'w, 's>,
+    filter: SpatialQueryFilter,
+    debug_casts: RefCell<Vec<DebugShapeCast>>,
+}
+
+impl<'a, 'w, 's> SpatialCollisionWorld<'a, 'w, 's> {
+    /// Creates a collision world that ignores `excluded_entities` (typically the player and its children).
+    pub fn new(spatial_query: &'a SpatialQuery<'w, 's>, excluded_entities: impl IntoIterator<Item = Entity>) -> Self {
+        Self {
+            spatial_query,
+            filter: SpatialQueryFilter::from_excluded_entities(excluded_entities),
+            debug_casts: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Draws and clears all buffered debug casts.
+    pub fn draw_debug(&self, gizmos: &mut Gizmos<'_, '_, PhysicsGizmos>) {
+        for cast in self.debug_casts.borrow_mut().drain(..) {
+            gizmos.draw_shapecast(
+                &cast.shape,
+                cast.origin,
+                Quat::default(),
+                cast.direction,
+                cast.distance,
+                &[],
+                cast.ray_color,
+                Color::srgb(1.0, 1.0, 0.0), // shape_color
+                Color::srgb(1.0, 0.0, 0.0), // point_color
+                Color::srgb(0.0, 1.0, 0.0), // normal_color
+                1.0,
+            );
+        }
+    }
+}
+
+impl CollisionWorld for SpatialCollisionWorld<'_, '_, '_> {
+    fn cast_shape(
+        &self,
+        shape: &Collider,
+        origin: Vec3,
+        rotation: Quat,
+        direction: Dir3,
+        max_distance: f32,
+    ) -> Option<ShapeHitData> {
+        self.spatial_query.cast_shape(
+            shape,
+            origin,
+            rotation,
+            direction,
+            &ShapeCastConfig { max_distance, ..Default::default() },
+            &self.filter,
+        )
+    }
+
+    fn shape_hits(
+        &self,
+        shape: &Collider,
+        origin: Vec3,
+        rotation: Quat,
+        direction: Dir3,
+        max_distance: f32,
+        max_hits: u32,
+    ) -> Vec<ShapeHitData> {
+        self.spatial_query.shape_hits(
+            shape,
+            origin,
+            rotation,
+            direction,
+            max_hits,
+            &ShapeCastConfig { max_distance, ..Default::default() },
+            &self.filter,
+        )
+    }
+
+    fn debug_shape_cast(&self, shape: &Collider, origin: Vec3, direction: Dir3, distance: f32, ray_color: Color) {
+        if crate::game::player::movement::debug::is_debug_shape_casts() {
+            self.debug_casts.borrow_mut().push(DebugShapeCast {
+                shape: shape.clone(),
+                origin,
+                direction,
+                distance,
+                ray_color,
+            });
+        }
+    }
+}
+
+/// Advances a player's movement state by one tick.
+///
+/// Runs the same stages, in the same order, as the fixed-schedule systems:
+/// `advance_timers`, `advance_wall_timers`, `update_crouch`, `check_water_jump`, `apply_input` and `kinematic_step`.
+pub fn step(
+    state: MovementState,
+    input: &MovementInput,
+    config: &MovementConfig,
+    dt: f32,
+    world: &dyn CollisionWorld,
+) -> MovementState {
+    let mut state = state;
+    state.clear_events();
+    advance_timers(&mut state, dt);
+    advance_wall_timers(&mut state, dt);
+    update_crouch(&mut state, input, world);
+    check_water_jump(&mut state, input, config, world);
+    apply_input(&mut state, input, config, dt);
+    kinematic_step(&mut state, config, dt, world);
+    state
+}
+
+/// Advances the jump, fall and slide timers.
+pub fn advance_timers(state: &mut MovementState, dt: f32) {
+    if state.grounded && state.fall_timer > 0.0 {
+        // Both timers have advanced by the same ticks since a jump that started this fall, so a jump during the
+        // fall is never older than the fall itself, while walking off a ledge leaves an older jump timer
+        state.landed_from_jump = state.just_jumped <= state.fall_timer;
+        state.landing_timer = 0.0;
+    } else if state.landing_timer < f32::MAX {
+        state.landing_timer += dt;
+    }
+    if state.just_jumped < f32::MAX {
+        state.just_jumped += dt;
+    }
+    if state.launch_timer < f32::MAX {
+        state.launch_timer += dt;
+    }
+    if state.grounded {
+        state.fall_timer = 0.0;
+    } else {
+        state.fall_timer += dt;
+    }
+    if state.jump_input_timer < f32::MAX {
+        state.jump_input_timer += dt;
+    }
+    if state.sliding {
+        state.slide_timer += dt;
+    }
+    state.slide_cooldown = (state.slide_cooldown - dt).max(0.0);
+}
+
+/// Advances the wall contact and wall-run timers.
+pub fn advance_wall_timers(state: &mut MovementState, dt: f32) {
+    if state.wall_timer < f32::MAX {
+        state.wall_timer += dt;
+    }
+    if state.wall_running {
+        state.wall_run_timer += dt;
+    }
+}
+
+/// Updates the crouch state from input.
+///
+/// Crouching is immediate; standing up only happens once the standing body would not hit a ceiling.
+pub fn update_crouch(state: &mut MovementState, input: &MovementInput, world: &dyn CollisionWorld) {
+    if input.crouch_pressed {
+        state.crouching = true;
+    } else if state.crouching && can_stand(state, world) {
+        state.crouching = false;
+    }
+}
+
+/// Returns true if there is room above a crouched player for the full standing body.
+fn can_stand(state: &MovementState, world: &dyn CollisionWorld) -> bool {
+    let hull = state.shape.hull();
+    let (_, center, height) = body_collider(state.translation, 0.0, true, state.shape);
+    // Slightly thinner probe so walls the player is already touching don't count as ceilings
+    let probe = state.shape.collider(hull.radius * 0.95, height);
+    let clearance = hull.body_full_height() - hull.crouch_body_full_height();
+    world.debug_shape_cast(&probe, center - Vec3::Y * (height * 0.5), Dir3::Y, clearance, Color::srgb(1.0, 0.5, 0.0));
+    world.cast_shape(&probe, center, Quat::default(), Dir3::Y, clearance).is_none()
+}
+
+/// Applies input-driven friction, acceleration and jumping.
+///
+/// A jump fires if a jump input is pending and the player is grounded, or left the ground without jumping less
+/// than `coyote_time` ago. What counts as pending depends on `MovementConfig::jump_mode`:
+/// - `HoldAutoHop`: jump held within `jump_buffer_time`, so holding jump hops on every landing.
+/// - `PressEdge`: jump pressed (not merely held) within `jump_buffer_time`.
+/// - `Queued`: jump pressed at any point since the last jump.
+///
+/// Wall jumps need a fresh press, or in the press modes a press made since the wall was last touched; holding jump
+/// never wall jumps.
+///
+/// On a ladder or while swimming, climbing or swimming movement replaces all of the above (see `ladder_move` and
+/// `water_move`).
+///
+/// Returns the world-space wish direction derived from the input.
+pub fn apply_input(state: &mut MovementState, input: &MovementInput, config: &MovementConfig, dt: f32) -> Vec3 {
+    let wish_vel = Vec3::new(input.movement_direction.x, 0.0, -input.movement_direction.y).normalize_or_zero();
+    // Use networked yaw rotation for consistent movement direction calculation across clients
+    let wish_dir = (Quat::from_rotation_y(input.yaw_rotation) * wish_vel).normalize_or_zero();
+    let mut wish_speed = input.movement_magnitude * config.speed;
+    if state.grounded && state.crouching {
+        wish_speed *= config.crouch_speed_scale;
+    }
+
+    if is_climbing(state) {
+        return ladder_move(state, input, config);
+    }
+    if is_swimming(state) {
+        return water_move(state, input, config, dt);
+    }
+    update_slide(state, config);
+    update_wall_run(state, config);
+    if state.sliding {
+        slide_move(state, config, dt);
+    } else if state.grounded {
+        apply_friction(&mut state.velocity, config, dt);
+        accelerate(
+            &mut state.velocity,
+            wish_dir,
+            wish_speed,
+            config.ground_accelerate,
+            dt,
+        );
+    } else {
+        air_move(&mut state.velocity, input, wish_dir, wish_speed, config, dt);
+    }
+    let jump_edge = input.jump_pressed && !state.jump_held;
+    state.jump_held = input.jump_pressed;
+    let jump_registered = match config.jump_mode {
+        JumpMode::HoldAutoHop => input.jump_pressed,
+        JumpMode::PressEdge | JumpMode::Queued => jump_edge,
+    };
+    if jump_registered {
+        state.jump_input_timer = 0.0;
+    }
+    let jump_buffered = match config.jump_mode {
+        JumpMode::Queued => state.jump_input_timer < f32::MAX,
+        JumpMode::HoldAutoHop | JumpMode::PressEdge => state.jump_input_timer <= config.jump_buffer_time,
+    };
+    // No jump or launch since leaving the ground means both timers are older than the fall timer
+    let coyote = !state.grounded
+        && state.fall_timer <= config.coyote_time
+        && state.just_jumped > state.fall_timer
+        && state.launch_timer > state.fall_timer;
+    let wall_jump = config.wall_jump_enabled
+        && !state.grounded
+        && !coyote
+        && state.wall_timer <= config.wall_jump_window
+        && state.wall_normal != Vec3::ZERO;
+    // Holding jump must not wall jump on every wall touched: only a fresh press, or a press buffered since the
+    // wall was touched, kicks off
+    let wall_jump_pressed = jump_edge
+        || (config.jump_mode != JumpMode::HoldAutoHop && jump_buffered && state.jump_input_timer <= state.wall_timer);
+    if wall_jump_pressed && wall_jump {
+        // Kick off the wall: drop the velocity into it, then push away and up
+        let into_wall = state.velocity.dot(state.wall_normal).min(0.0);
+        state.velocity -= state.wall_normal * into_wall;
+        state.velocity += state.wall_normal * config.wall_jump_push;
+        state.velocity.y = state.velocity.y.max(0.0) + config.wall_jump_power;
+        state.wall_running = false;
+        state.wall_timer = f32::MAX; // One wall jump per contact
+        state.just_jumped = 0.0;
+        state.jump_input_timer = f32::MAX;
+        state.jumped = Some(JumpKind::Wall);
+    } else if jump_buffered && (state.grounded || coyote) {
+        // Jumping off a moving platform carries its momentum
+        state.velocity.x += state.platform_velocity.x;
+        state.velocity.z += state.platform_velocity.z;
+        let mut jump_power = config.jump_power;
+        if state.sliding {
+            // Slide-jump: keeps the slide speed and ends the slide
+            jump_power *= config.slide_jump_scale;
+            end_slide(state, config);
+        }
+        // CPMA double jump: jumping again shortly after landing from a jump (walking off a ledge doesn't count)
+        if state.landed_from_jump && state.landing_timer <= config.double_jump_window {
+            jump_power += config.double_jump_power;
+        }
+        // CPMA ramp jump: running up a ramp adds its upward speed to the jump. Grounded vertical velocity is zeroed
+        // after every move, so the speed comes from the horizontal velocity along the ground
+        let ramp_speed = if config.ramp_jump && state.grounded {
+            slope_vertical_speed(state.velocity, state.ground_normal).max(0.0)
+        } else {
+            0.0
+        };
+        state.velocity.y = ramp_speed + jump_power + state.platform_velocity.y;
+        state.grounded = false; // Immediately become ungrounded when jumping
+        state.just_jumped = 0.0;
+        state.jump_input_timer = f32::MAX; // Consume the buffered press
+        state.jumped = Some(JumpKind::Ground);
+    }
+    wish_dir
+}
+
+/// Returns true if the player is on a ladder and not in the middle of jumping off it.
+fn is_climbing(state: &MovementState) -> bool {
+    /// Seconds after a jump before a ladder can be grabbed again, so dismount jumps leave the ladder volume.
+    const LADDER_REGRAB_TIME: f32 = 0.3;
+    state.on_ladder && state.just_jumped > LADDER_REGRAB_TIME
+}
+
+/// Ladder movement, replacing friction, acceleration and gravity.
+///
+/// Input moves the player directly along the view direction including pitch, so looking up while moving forward
+/// climbs and looking down descends. Jumping pushes the player off the ladder. Returns the wish direction.
+fn ladder_move(state: &mut MovementState, input: &MovementInput, config: &MovementConfig) -> Vec3 {
+    let view = Quat::from_rotation_y(input.yaw_rotation) * Quat::from_rotation_x(input.pitch_rotation);
+    let forward = view * Vec3::NEG_Z;
+    let right = Quat::from_rotation_y(input.yaw_rotation) * Vec3::X;
+    let wish_dir = (forward * input.movement_direction.y + right * input.movement_direction.x).normalize_or_zero();
+    state.velocity = wish_dir * input.movement_magnitude * config.ladder_climb_speed;
+    state.sliding = false;
+    state.wall_running = false;
+    if state.velocity.y > 0.0 {
+        state.grounded = false;
+    }
+
+    let jump_edge = input.jump_pressed && !state.jump_held;
+    state.jump_held = input.jump_pressed;
+    if jump_edge {
+        let away = if state.ladder_normal != Vec3::ZERO {
+            state.ladder_normal
+        } else {
+            -Vec3::new(forward.x, 0.0, forward.z).normalize_or_zero()
+        };
+        state.velocity = away * config.ladder_jump_push + Vec3::Y * config.ladder_jump_power;
+        state.grounded = false;
+        state.just_jumped = 0.0;
+        state.jump_input_timer = f32::MAX;
+        state.jumped = Some(JumpKind::Ladder);
+    }
+    wish_dir
+}
+
+/// Returns how deep the player is in a liquid.
+pub fn current_water_level(state: &MovementState) -> WaterLevel {
+    state.liquid_surface.map_or(WaterLevel::None, |surface| {
+        water_level(state.translation, surface, state.crouching, &state.shape.hull())
+    })
+}
+
+/// Returns true if the player is at least waist-deep in a liquid.
+fn is_swimming(state: &MovementState) -> bool {
+    current_water_level(state) >= WaterLevel::Waist
+}
+
+/// Swimming movement, replacing friction, acceleration and jumping (Quake's `PM_WaterMove`).
+///
+/// - Drag slows the player down on all axes.
+/// - Input swims along the view direction including pitch, at `swim_speed_scale` of the ground speed.
+/// - Holding jump swims up; without any input the player slowly sinks.
+///
+/// Returns the wish direction.
+fn water_move(state: &mut MovementState, input: &MovementInput, config: &MovementConfig, dt: f32) -> Vec3 {
+    let speed = state.velocity.length();
+    if speed > 0.0 {
+        let new_speed = (speed - speed * config.water_friction * dt).max(0.0);
+        state.velocity *= new_speed / speed;
+    }
+
+    let view = Quat::from_rotation_y(input.yaw_rotation) * Quat::from_rotation_x(input.pitch_rotation);
+    let forward = view * Vec3::NEG_Z;
+    let right = Quat::from_rotation_y(input.yaw_rotation) * Vec3::X;
+    let swim_speed = config.speed * config.swim_speed_scale;
+    let mut wish_vel = (forward * input.movement_direction.y + right * input.movement_direction.x).normalize_or_zero()
+        * input.movement_magnitude
+        * swim_speed;
+    if input.jump_pressed {
+        wish_vel.y += swim_speed;
+    } else if wish_vel == Vec3::ZERO {
+        wish_vel.y -= config.swim_sink_speed;
+    }
+    state.jump_held = input.jump_pressed;
+    state.sliding = false;
+    state.wall_running = false;
+
+    let wish_dir = wish_vel.normalize_or_zero();
+    let wish_speed = wish_vel.length().min(swim_speed);
+    let add_speed = wish_speed - state.velocity.dot(wish_dir);
+    if add_speed > 0.0 {
+        let accel_speed = (config.water_accelerate * wish_speed * dt).min(add_speed);
+        state.velocity += wish_dir * accel_speed;
+    }
+    if state.velocity.y > 0.0 {
+        state.grounded = false;
+    }
+    wish_dir
+}
+
+/// Launches a waist-deep player out of the liquid onto a ledge in front of them (Quake's `PM_CheckWaterJump`).
+///
+/// Fires when moving forward with a wall at waist height and free space at eye height in front of the player.
+pub fn check_water_jump(state: &mut MovementState, input: &MovementInput, config: &MovementConfig, world: &dyn CollisionWorld) {
+    /// How far in front of the body the ledge is probed.
+    const WATER_JUMP_PROBE_DISTANCE: f32 = 0.3;
+    if current_water_level(state) != WaterLevel::Waist || input.movement_direction.y <= 0.0 || state.just_jumped < 0.5 {
+        return;
+    }
+    let Ok(forward) = Dir3::new(Quat::from_rotation_y(input.yaw_rotation) * Vec3::NEG_Z) else {
+        return;
+    };
+    let probe = Collider::sphere(0.1);
+    let hull = state.shape.hull();
+    let feet = hull.feet(state.translation);
+    let waist = Vec3::new(state.translation.x, feet + hull.height * 0.5, state.translation.z);
+    let eyes = Vec3::new(state.translation.x, feet + hull.height * 0.9, state.translation.z);
+    let distance = hull.radius + WATER_JUMP_PROBE_DISTANCE;
+    world.debug_shape_cast(&probe, waist, forward, distance, Color::srgb(0.0, 0.5, 1.0));
+    if world.cast_shape(&probe, waist, Quat::default(), forward, distance).is_none() {
+        return;
+    }
+    world.debug_shape_cast(&probe, eyes, forward, distance, Color::srgb(0.0, 0.5, 1.0));
+    if world.cast_shape(&probe, eyes, Quat::default(), forward, distance).is_some() {
+        return;
+    }
+    state.velocity = *forward * config.water_jump_push + Vec3::Y * config.water_jump_power;
+    state.grounded = false;
+    state.just_jumped = 0.0;
+    state.jumped = Some(JumpKind::Water);
+}
+
+/// Starts or ends a wall run.
+///
+/// - Starts when airborne, touching a wall and moving faster than `wall_run_min_speed` horizontally.
+/// - Ends when grounded, once the wall is lost, the player slows down, or after `wall_run_max_duration`
+///   (which only refills on landing).
+fn update_wall_run(state: &mut MovementState, config: &MovementConfig) {
+    /// Seconds without wall contact before a wall run ends.
+    const WALL_CONTACT_GRACE: f32 = 0.1;
+    if state.grounded {
+        state.wall_running = false;
+        state.wall_run_timer = 0.0;
+        return;
+    }
+    let speed = Vec3::new(state.velocity.x, 0.0, state.velocity.z).length();
+    let can_run = config.wall_run_enabled
+        && state.wall_normal != Vec3::ZERO
+        && state.wall_timer <= WALL_CONTACT_GRACE
+        && speed >= config.wall_run_min_speed
+        && state.wall_run_timer < config.wall_run_max_duration;
+    if can_run && !state.wall_running {
+        // Catch the player so the run doesn't start mid-fall
+        state.velocity.y = state.velocity.y.max(0.0);
+    }
+    state.wall_running = can_run;
+}
+
+/// Starts or ends a crouch-slide.
+///
+/// - Starts when grounded and crouching above `slide_min_speed`, once the cooldown has run out.
+/// - Ends when leaving the ground, standing up, after `slide_duration`, or once slowed down to crouch-walking speed.
+fn update_slide(state: &mut MovementState, config: &MovementConfig) {
+    let speed = Vec3::new(state.velocity.x, 0.0, state.velocity.z).length();
+    if state.sliding {
+        let slowed = speed <= config.speed * config.crouch_speed_scale;
+        if !state.grounded || !state.crouching || state.slide_timer >= config.slide_duration || slowed {
+            end_slide(state, config);
+        }
+    } else if config.slide_enabled
+        && state.grounded
+        && state.crouching
+        && state.slide_cooldown <= 0.0
+        && speed >= config.slide_min_speed
+    {
+        state.sliding = true;
+        state.slide_timer = 0.0;
+    }
+}
+
+/// Ends the current slide and starts the slide cooldown.
+fn end_slide(state: &mut MovementState, config: &MovementConfig) {
+    state.sliding = false;
+    state.slide_cooldown = config.slide_cooldown;
+}
+
+/// Ground movement while sliding: no input acceleration, `slide_friction`, and gravity pulling down slopes.
+fn slide_move(state: &mut MovementState, config: &MovementConfig, dt: f32) {
+    apply_friction_amount(&mut state.velocity, config.slide_friction, dt);
+    let normal = state.ground_normal;
+    if normal != Vec3::ZERO {
+        // Component of gravity along the ground plane, pointing downhill
+        let gravity = Vec3::NEG_Y * config.gravity;
+        let downhill = gravity - normal * gravity.dot(normal);
+        state.velocity.x += downhill.x * config.slide_slope_scale * dt;
+        state.velocity.z += downhill.z * config.slide_slope_scale * dt;
+    }
+}
+
+/// Applies airborne acceleration using the configured `AirMovementModel`.
+///
+/// - `Vq3`: plain acceleration with `air_accelerate`.
+/// - `Qw`: wish speed capped at `air_wish_speed_cap` while the acceleration rate uses the uncapped wish speed,
+///   which is what makes QuakeWorld air strafing gain speed.
+/// - `Cpm`: `air_stop_accelerate` when braking, strafe-only input accelerates with `strafe_accelerate` up to
+///   `air_wish_speed_cap`, and pure forward/back input steers velocity with `air_control`.
+fn air_move(
+    velocity: &mut Vec3,
+    input: &MovementInput,
+    wish_dir: Vec3,
+    wish_speed: f32,
+    config: &MovementConfig,
+    dt: f32,
+) {
+    match config.air_model {
+        AirMovementModel::Vq3 => {
+            accelerate(velocity, wish_dir, wish_speed, config.air_accelerate, dt);
+        }
+        AirMovementModel::Qw => {
+            let current_speed = velocity.dot(wish_dir);
+            let add_speed = wish_speed.min(config.air_wish_speed_cap) - current_speed;
+            if add_speed <= 0.0 {
+                return;
+            }
+            let accel_speed = (config.air_accelerate * wish_speed * dt).min(add_speed);
+            velocity.x += accel_speed * wish_dir.x;
+            velocity.z += accel_speed * wish_dir.z;
+        }
+        AirMovementModel::Cpm => {
+            let forward_move = input.movement_direction.y;
+            let strafe_only = forward_move.abs() <= f32::EPSILON && input.movement_direction.x.abs() > f32::EPSILON;
+            let (accel, capped_wish_speed) = if strafe_only {
+                (config.strafe_accelerate, wish_speed.min(config.air_wish_speed_cap))
+            } else if velocity.dot(wish_dir) < 0.0 {
+                (config.air_stop_accelerate, wish_speed)
+            } else {
+                (config.air_accelerate, wish_speed)
+            };
+            accelerate(velocity, wish_dir, capped_wish_speed, accel, dt);
+            // Only pure forward/back input steers, so strafe jumping with forward + strafe isn't rotated
+            let forward_only = forward_move.abs() > f32::EPSILON && input.movement_direction.x.abs() <= f32::EPSILON;
+            if forward_only && wish_speed > 0.0 {
+                air_control(velocity, wish_dir, config.air_control, dt);
+            }
+        }
+    }
+}
+
+/// CPM air control: rotates horizontal velocity towards the wish direction without changing speed.
+///
+/// CPM's factor of 32 is in Quake units, so it is scaled to meters to turn as fast as CPM at the same speeds.
+fn air_control(velocity: &mut Vec3, wish_dir: Vec3, air_control: f32, dt: f32) {
+    let horizontal = Vec3::new(velocity.x, 0.0, velocity.z);
+    let speed = horizontal.length();
+    let direction = horizontal.normalize_or_zero();
+    let dot = direction.dot(wish_dir);
+    if dot <= 0.0 {
+        return;
+    }
+    let k = 32.0 * QUAKE_UNIT_SCALE * air_control * dot * dot * dt;
+    let steered = (direction * speed + wish_dir * k).normalize_or_zero();
+    velocity.x = steered.x * speed;
+    velocity.z = steered.z * speed;
+}
+
+/// Applies gravity, ground/step detection and collide-and-slide.
+///
+/// While grounded on a moving platform the player is carried horizontally by `platform_velocity`; vertical platform
+/// motion is followed by the ground snapping. Leaving the platform without jumping keeps its momentum.
+///
+/// With `StepMode::StepSlide` the move goes through `step_slide_move` instead of plain collide-and-slide.
+///
+/// Reports landings, walking off ledges, step-ups and wall impacts in the state's event fields.
+pub fn kinematic_step(state: &mut MovementState, config: &MovementConfig, dt: f32, world: &dyn CollisionWorld) {
+    let was_grounded = state.grounded;
+    apply_gravity(state, config, dt);
+    let ground_cast_distance = ground_and_step_normalization(state, config, dt, world);
+    if was_grounded && !state.grounded {
+        state.velocity += state.platform_velocity;
+        state.left_ground = true;
+    }
+    let carry = if state.grounded {
+        Vec3::new(state.platform_velocity.x, 0.0, state.platform_velocity.z) * dt
+    } else {
+        Vec3::ZERO
+    };
+    match config.step_mode {
+        StepMode::Smoothed => {
+            collision_and_slide(state, config, dt, ground_cast_distance, carry, world);
+        }
+        StepMode::StepSlide => step_slide_move(state, config, dt, ground_cast_distance, carry, world),
+    }
+    if state.wall_running {
+        probe_wall(state, config, world);
+    }
+}
+
+/// Distance (meters) probed towards the wall to keep a wall run's contact alive.
+const WALL_PROBE_DISTANCE: f32 = 0.1;
+
+/// Refreshes the wall contact of a wall-running player with a short cast towards the wall.
+///
+/// Running parallel to a wall never moves into it, so without the probe the contact recorded by the slide would
+/// expire after a few ticks and end the run. Touching the wall again this way is not reported as a `wall_hit`.
+fn probe_wall(state: &mut MovementState, config: &MovementConfig, world: &dyn CollisionWorld) {
+    let Ok(direction) = Dir3::new(-state.wall_normal) else {
+        return;
+    };
+    let (collider, origin, height) = body_collider(state.translation, 0.0, state.crouching, state.shape);
+    world.debug_shape_cast(
+        &collider,
+        origin - Vec3::Y * (height * 0.5),
+        direction,
+        WALL_PROBE_DISTANCE,
+        Color::srgb(1.0, 0.5, 0.0),
+    );
+    let Some(hit) = world.cast_shape(&collider, origin, Quat::default(), direction, WALL_PROBE_DISTANCE) else {
+        return;
+    };
+    // Same wall criteria as the slide: roughly vertical surfaces that are neither walkable nor surfable
+    if is_walkable(hit.normal1, config) || is_surfable(hit.normal1, config) || hit.normal1.y.abs() >= 0.7 {
+        return;
+    }
+    state.wall_normal = slide_plane(hit.normal1, config);
+    state.wall_entity = Some(hit.entity);
+    state.wall_timer = 0.0;
+}
+
+/// Adds an external impulse (knockback) to the player's velocity.
+///
+/// An impulse that leaves the player moving upwards ungrounds them and restarts `launch_timer`, so the ground
+/// snapping in `kinematic_step` doesn't pull them straight back down. It doesn't count as a jump.
+pub fn apply_impulse(state: &mut MovementState, impulse: Vec3) {
+    state.velocity += impulse;
+    if impulse.y > 0.0 && state.velocity.y > 0.0 {
+        state.grounded = false;
+        state.launch_timer = 0.0;
+    }
+}
+
+/// Applies gravity to the player if not grounded, scaled by `wall_run_gravity_scale` while wall running.
+///
+/// Players climbing a ladder are not affected by gravity, and buoyancy cancels `liquid_buoyancy` of it for swimmers.
+fn apply_gravity(state: &mut MovementState, config: &MovementConfig, dt: f32) {
+    if !state.grounded && !is_climbing(state) {
+        let scale = if is_swimming(state) {
+            1.0 - config.liquid_buoyancy
+        } else if state.wall_running {
+            config.wall_run_gravity_scale
+        } else {
+            1.0
+        };
+        state.velocity.y -= config.gravity * scale * dt;
+    }
+}
+
+/// Step-ups lower than this (meters) are ground unevenness rather than steps and are not reported.
+const STEP_EVENT_MIN_HEIGHT: f32 = 0.05;
+
+/// Handles ground detection and step normalization.
+///
+/// - Records the ground normal in `state.ground_normal`.
+/// - Reports a `landing` whenever this grounds a player that was airborne.
+/// - Ground steeper than `max_slope_angle` never grounds the player; velocity into it is clipped so gravity
+///   slides the player down.
+/// - On walkable slopes, vertical velocity follows the slope so horizontal movement stays on the surface.
+/// - Landing on a ramp while moving up it faster than `rampslide_speed` keeps the player sliding instead of
+///   snapping to the ground, and running up a ramp faster than `trimp_speed` launches the player (trimping).
+///
+/// Returns the distance of the ground cast hit, if any.
+fn ground_and_step_normalization(
+    state: &mut MovementState,
+    config: &MovementConfig,
+    dt: f32,
+    world: &dyn CollisionWorld,
+) -> Option<f32> {
+    let previous_step_offset = state.step_offset;
+    state.step_offset = 0.0;
+    // Use only the jump and launch timers to determine if we should skip ground logic
+    // Increased threshold for proper jump grace period
+    let just_jumped_active = state.just_jumped < 0.05 || state.launch_timer < 0.05;
+    // Climbing a ladder or swimming up must not be snapped back to the floor below
+    let climbing_up = (is_climbing(state) || is_swimming(state)) && state.velocity.y > 0.0;
+    if just_jumped_active || climbing_up {
+        state.grounded = false;
+        state.ground_normal = Vec3::ZERO;
+        state.ground_entity = None;
+        return None;
+    }
+    // The cast origin is the center of the player collider minus half the collider height on Y.
+    // This ensures the ground cast is always aligned with the collider, regardless of state.
+    let hull = state.shape.hull();
+    let step_height = hull.step_height;
+    let cast_start = state.translation - Vec3::Y * (hull.body_full_height() * 0.5);
+    let cast_direction = Dir3::NEG_Y;
+    let max_step_distance = step_height * 2.0;
+    let (ground_shape, shape_offset) = state.shape.ground_cast_collider();
+    let cast_origin = cast_start + Vec3::Y * shape_offset;
+    world.debug_shape_cast(&ground_shape, cast_origin, cast_direction, max_step_distance, Color::srgb(0.0, 1.0, 1.0));
+    let Some(hit) = world.cast_shape(&ground_shape, cast_origin, Quat::default(), cast_direction, max_step_distance) else {
+        state.grounded = false;
+        state.ground_normal = Vec3::ZERO;
+        state.ground_entity = None;
+        return None;
+    };
+    state.ground_normal = hit.normal1;
+    state.ground_entity = Some(hit.entity);
+
+    if !is_walkable(hit.normal1, config) {
+        // Too steep to stand on: stay airborne and slide down along the surface
+        state.grounded = false;
+        if hit.distance <= step_height {
+            clip_velocity_into(&mut state.velocity, hit.normal1);
+        }
+        return Some(hit.distance);
+    }
+
+    let diff = step_height - hit.distance;
+    let air_time = state.fall_timer;
+    let was_grounded = state.grounded;
+    let impact_speed = (-state.velocity.y).max(0.0);
+    let slope_speed = slope_vertical_speed(state.velocity, hit.normal1);
+    if hit.distance <= step_height && air_time > 0.05 {
+        let mut along_surface = state.velocity;
+        clip_velocity_into(&mut along_surface, hit.normal1);
+        if along_surface.y > config.rampslide_speed {
+            // Rampslide: moving up the ramp fast enough to keep sliding along it instead of landing
+            state.velocity = along_surface;
+            state.grounded = false;
+        } else {
+            // Snap player so ground cast distance is exactly the step height
+            let correction = cast_direction * (hit.distance - step_height);
+            state.translation += correction;
+            state.velocity.y = slope_speed;
+            state.grounded = true;
+        }
+    } else if air_time <= 0.01 && slope_speed > config.trimp_speed {
+        // Trimp: running up a ramp fast enough converts horizontal speed into height
+        state.velocity.y = slope_speed * config.trimp_multiplier;
+        state.grounded = false;
+    } else if air_time <= 0.01 && diff.abs() > 0.01 && config.step_mode == StepMode::StepSlide {
+        // Steps are climbed by `step_slide_move`; follow the ground at once, stepping down stairs like Quake
+        state.translation.y += diff;
+        state.velocity.y = slope_speed;
+        state.grounded = true;
+    } else if air_time <= 0.01 && diff.abs() > 0.01 {
+        // Only allow step smoothing if not falling
+        let horizontal_speed = state.velocity.xz().length();
+        let calculated_step_speed = config.step_climb_speed + (horizontal_speed * horizontal_speed * 0.07);
+        let dynamic_step_speed = calculated_step_speed.max(config.min_step_speed);
+        // Let the player keep up with elevators moving faster than the step speed
+        let max_step_delta = (dynamic_step_speed + state.platform_velocity.y.abs()) * dt;
+        let movement_to_apply = if diff > 0.0 {
+            diff.min(max_step_delta)
+        } else {
+            diff.max(-max_step_delta)
+        };
+        // Ground rising by more than what was left of the previous step is a new step
+        let new_step_height = diff - previous_step_offset.max(0.0);
+        if new_step_height > STEP_EVENT_MIN_HEIGHT {
+            state.stepped_up = Some(new_step_height);
+        }
+        state.step_offset = diff - movement_to_apply;
+        state.velocity.y = movement_to_apply / dt + slope_speed;
+    } else if diff.abs() <= 0.01 {
+        state.velocity.y = slope_speed;
+        state.grounded = true;
+    }
+    // Whichever branch grounded an airborne player, that is a landing
+    if !was_grounded && state.grounded {
+        state.landing = Some(Landing { impact_speed, air_time });
+    }
+    Some(hit.distance)
+}
+
+/// Returns true if a surface with this normal is shallow enough to stand on.
+pub fn is_walkable(normal: Vec3, config: &MovementConfig) -> bool {
+    normal.y >= config.min_walkable_normal_y()
+}
+
+/// Returns true if surfing is enabled and a surface with this normal is a surf ramp:
+/// too steep to stand on but still facing upwards.
+///
+/// Surf ramps never ground the player, so there is no friction and velocity is clipped against the true normal.
+pub fn is_surfable(normal: Vec3, config: &MovementConfig) -> bool {
+    config.surf_enabled && normal.y > 0.0 && !is_walkable(normal, config)
+}
+
+/// Vertical speed needed for horizontal `velocity` to follow a surface with the given normal.
+fn slope_vertical_speed(velocity: Vec3, normal: Vec3) -> f32 {
+    if normal.y <= f32::EPSILON {
+        return 0.0;
+    }
+    -(normal.x * velocity.x + normal.z * velocity.z) / normal.y
+}
+
+/// Removes the component of `velocity` pointing into a surface with the given normal.
+fn clip_velocity_into(velocity: &mut Vec3, normal: Vec3) {
+    let into_surface = velocity.dot(normal);
+    if into_surface < 0.0 {
+        *velocity -= normal * into_surface;
+    }
+}
+
+/// Maximum number of planes `collision_and_slide` clips against in one move, as in Quake.
+const MAX_CLIP_PLANES: usize = 5;
+
+/// Returns the plane the player slides along after hitting a surface with the given normal.
+///
+/// Walkable surfaces (floors, ramps) and surf ramps are slid along their true plane. Steeper surfaces are treated as
+/// perfectly vertical walls, which also hides the slanted normals reported by cylinder rims and capsule caps catching
+/// on edges. Ceilings, which have no horizontal component, keep their true normal.
+fn slide_plane(normal: Vec3, config: &MovementConfig) -> Vec3 {
+    if is_walkable(normal, config) || is_surfable(normal, config) {
+        return normal;
+    }
+    let flat_normal = Vec3::new(normal.x, 0.0, normal.z).normalize_or_zero();
+    if flat_normal.length_squared() < 1e-5 {
+        normal
+    } else {
+        flat_normal
+    }
+}
+
+/// Clips `velocity` so it doesn't move into any of `planes`, as in Quake's `PM_SlideMove`.
+///
+/// The velocity is clipped against the first plane it moves into, then against every other plane the result still
+/// moves into. If clipping against a second plane turns it back into the first, the two planes form a crease and
+/// the velocity is projected onto their cross product; if that still moves into a third plane the player is wedged
+/// in a corner and stops dead.
+fn clip_velocity_to_planes(velocity: Vec3, planes: &[Vec3]) -> Vec3 {
+    const EPSILON: f32 = 1e-5;
+    for (i, &plane) in planes.iter().enumerate() {
+        if velocity.dot(plane) >= -EPSILON {
+            continue;
+        }
+        let mut clipped = velocity;
+        clip_velocity_into(&mut clipped, plane);
+        for (j, &other) in planes.iter().enumerate() {
+            if j == i || clipped.dot(other) >= -EPSILON {
+                continue;
+            }
+            clip_velocity_into(&mut clipped, other);
+            if clipped.dot(plane) >= -EPSILON {
+                continue;
+            }
+            // The second clip pushed back into the first plane: slide along the crease of both
+            let crease = plane.cross(other).normalize_or_zero();
+            clipped = crease * crease.dot(velocity);
+            let wedged = planes
+                .iter()
+                .enumerate()
+                .any(|(k, &third)| k != i && k != j && clipped.dot(third) < -EPSILON);
+            if wedged {
+                return Vec3::ZERO;
+            }
+        }
+        return clipped;
+    }
+    velocity
+}
+
+/// Returns the player body collider, its center and its height.
+///
+/// Dimensions come from the shape's `PlayerHull`. `extension` lowers the bottom of the body below the regular body
+/// (the airborne extended collider, or the full hull for `StepMode::StepSlide`); crouching lowers the top of the
+/// body to `crouch_body_full_height`. The bottom of the regular body stays fixed relative to `translation`.
+fn body_collider(translation: Vec3, extension: f32, crouching: bool, shape: PlayerShape) -> (Collider, Vec3, f32) {
+    let hull = shape.hull();
+    let top = if crouching {
+        hull.crouch_body_full_height() - hull.body_full_height() * 0.5
+    } else {
+        hull.body_full_height() * 0.5
+    };
+    let bottom = -hull.body_full_height() * 0.5 - extension;
+    let height = top - bottom;
+    (
+        shape.collider(hull.radius, height),
+        translation + Vec3::Y * ((top + bottom) * 0.5),
+        height,
+    )
+}
+
+/// Performs collision detection and sliding for the player body.
+///
+/// Walls hit along the way are recorded in `wall_normal`, `wall_entity` and `wall_timer`.
+///
+/// `carry` is an extra displacement for this tick (e.g. from a moving platform) that is swept together with the
+/// player's own velocity but not added to it.
+///
+/// - Applies penetration correction (magenta debug shape when airborne, red when grounded).
+/// - Performs shape casts for collide-and-slide using the same `PlayerShape`, center and height as the collider.
+/// - Slides along walkable surfaces using their true normal; steeper surfaces are treated as vertical walls,
+///   unless surfing is enabled and the surface is a surfable ramp.
+/// - Clips against every plane hit during the move like Quake's `PM_SlideMove` (see `clip_velocity_to_planes`),
+///   sliding along the crease of two planes and stopping dead when wedged against three.
+/// - Prevents velocity snapping/zeroing when moving upward (only applies when falling).
+/// - Debug shapes are offset by `-Vec3::Y * (cast_height * 0.5)` for correct mesh alignment.
+///
+/// Returns true if the move was blocked by anything.
+fn collision_and_slide(
+    state: &mut MovementState,
+    config: &MovementConfig,
+    dt: f32,
+    ground_cast_distance: Option<f32>,
+    carry: Vec3,
+    world: &dyn CollisionWorld,
+) -> bool {
+    const EPSILON: f32 = 1e-5; // Increased for more robust overlap detection
+    const MAX_COLLISION_ITERATIONS: u32 = 5;
+    const PENETRATION_OFFSET: f32 = 0.002; // Smaller offset to reduce jitter
+    const WALL_HIT_REPEAT_TIME: f32 = 0.1; // Continuous contact within this time is not a new impact
+    let mut iterations = 0;
+    let mut vel = state.velocity * dt + carry;
+    let original_move = vel;
+    let mut planes: Vec<Vec3> = Vec::with_capacity(MAX_CLIP_PLANES);
+    let mut blocked = false;
+
+    // Penetration correction (Linahan), using the extended collider while airborne
+    let extension = body_extension(state, config, ground_cast_distance);
+    let (collider, collider_origin, height) = body_collider(state.translation, extension, state.crouching, state.shape);
+    let color = if extension > 0.0 {
+        Color::srgb(1.0, 0.0, 1.0)
+    } else {
+        Color::srgb(1.0, 0.0, 0.0)
+    };
+    world.debug_shape_cast(&collider, collider_origin - Vec3::Y * (height * 0.5), Dir3::Y, 0.0, color);
+    let contacts = world.shape_hits(&collider, collider_origin, Quat::default(), Dir3::NEG_Y, 0.0, 8);
+    let mut max_penetration = 0.0;
+    let mut correction = Vec3::ZERO;
+    for contact in &contacts {
+        if contact.distance < -EPSILON && -contact.distance > max_penetration {
+            max_penetration = -contact.distance;
+            correction = contact.normal2 * (max_penetration + PENETRATION_OFFSET);
+        }
+    }
+    if max_penetration > EPSILON {
+        state.translation += correction;
+    }
+
+    while vel.length() > EPSILON && iterations < MAX_COLLISION_ITERATIONS {
+        let direction = vel.normalize_or_zero();
+        let distance = vel.length();
+        if direction.length_squared() == 0.0 {
+            break;
+        }
+        let (cast_collider, cast_origin, cast_height) = body_collider(state.translation, extension, state.crouching, state.shape);
+        world.debug_shape_cast(
+            &cast_collider,
+            cast_origin - Vec3::Y * (cast_height * 0.5),
+            Dir3::new_unchecked(direction),
+            distance,
+            Color::srgb(0.0, 1.0, 1.0),
+        );
+        let hits = world.shape_hits(
+            &cast_collider,
+            cast_origin,
+            Quat::default(),
+            Dir3::new_unchecked(direction),
+            distance * 1.1,
+            8,
+        );
+        if hits.is_empty() {
+            state.translation += vel;
+            break;
+        }
+        let mut min_dist = f32::MAX;
+        let mut collision_point = state.translation;
+        let mut collision_normal = Vec3::ZERO;
+        let mut collision_entity = None;
+        for hit in &hits {
+            if hit.distance < min_dist && hit.distance >= 0.0 {
+                min_dist = hit.distance;
+                collision_point = state.translation + direction * hit.distance;
+                collision_normal = hit.normal1;
+                collision_entity = Some(hit.entity);
+            }
+        }
+        let collision_time = min_dist / distance;
+        if collision_time > 1.0 || collision_time < -EPSILON {
+            state.translation += vel;
+            break;
+        }
+        blocked = true;
+        let walkable = is_walkable(collision_normal, config);
+        let slide_normal = slide_plane(collision_normal, config);
+        // Only roughly vertical surfaces count as walls, not ceilings
+        if !walkable && !is_surfable(collision_normal, config) && collision_normal.y.abs() < 0.7 {
+            let speed = -state.velocity.dot(slide_normal);
+            if state.wall_hit.is_none() && state.wall_timer > WALL_HIT_REPEAT_TIME && speed > 0.0 {
+                state.wall_hit = Some(WallHit { normal: slide_normal, speed, entity: collision_entity });
+            }
+            state.wall_normal = slide_normal;
+            state.wall_entity = collision_entity;
+            state.wall_timer = 0.0;
+        }
+        // Landing on a walkable surface grounds the player
+        if walkable && state.velocity.y < 0.0 {
+            // Falls too fast for the ground cast to catch land here instead
+            if !state.grounded && state.landing.is_none() && state.fall_timer > 0.05 {
+                state.landing = Some(Landing { impact_speed: -state.velocity.y, air_time: state.fall_timer });
+            }
+            state.grounded = true;
+        }
+        state.translation = collision_point + slide_normal * PENETRATION_OFFSET;
+        // Every surface touched at the point of impact is a clip plane, so corners hit head-on clip against both walls
+        for hit in &hits {
+            if hit.distance < 0.0 || hit.distance > min_dist + PENETRATION_OFFSET {
+                continue;
+            }
+            let plane = slide_plane(hit.normal1, config);
+            // Hitting the same plane again only needs the clip already applied against it
+            if planes.iter().any(|&other| other.dot(plane) > 0.99) {
+                continue;
+            }
+            if planes.len() >= MAX_CLIP_PLANES {
+                // Wedged between too many surfaces: stop dead like Quake
+                state.velocity = Vec3::ZERO;
+                vel = Vec3::ZERO;
+                break;
+            }
+            planes.push(plane);
+        }
+        if vel == Vec3::ZERO {
+            break;
+        }
+        vel = clip_velocity_to_planes(vel * (1.0 - collision_time), &planes);
+        state.velocity = clip_velocity_to_planes(state.velocity, &planes);
+        // Turned back against the original move: stop instead of oscillating in a corner
+        if vel.dot(original_move) <= 0.0 {
+            state.velocity = Vec3::ZERO;
+            break;
+        }
+        iterations += 1;
+    }
+    // Clamp Y for safety
+    state.translation.y = state.translation.y.clamp(-100.0, 1000.0);
+    assert!(state.translation.is_finite(), "Player translation is not finite after movement: {:?}", state.translation);
+    assert!(state.velocity.is_finite(), "Player velocity is not finite after movement: {:?}", state.velocity);
+    if state.grounded {
+        state.velocity.y = 0.0;
+        let ground_y = (state.translation.y / 0.01).round() * 0.01;
+        if (state.translation.y - ground_y).abs() < 0.02 {
+            state.translation.y = ground_y;
+        }
+    }
+    blocked
+}
+
+/// Quake's `PM_StepSlideMove`: slides the player, and if the move was blocked, also tries it stepped up.
+///
+/// The stepped try lifts the body by the step height (less if a ceiling is in the way), slides, and drops back down
+/// onto the step, grounding the player on it. Whichever try went further horizontally is kept, so stairs are climbed
+/// in a single tick. The stepped try is discarded if it ends on ground too steep to stand on, and never attempted while moving upwards in
+/// the air, climbing a ladder or swimming.
+fn step_slide_move(
+    state: &mut MovementState,
+    config: &MovementConfig,
+    dt: f32,
+    ground_cast_distance: Option<f32>,
+    carry: Vec3,
+    world: &dyn CollisionWorld,
+) {
+    let start = *state;
+    if !collision_and_slide(state, config, dt, ground_cast_distance, carry, world) {
+        return;
+    }
+    if (start.velocity.y > 0.0 && !start.grounded) || is_climbing(&start) || is_swimming(&start) {
+        return;
+    }
+    let hull = start.shape.hull();
+    let extension = body_extension(&start, config, ground_cast_distance);
+    let (collider, center, height) = body_collider(start.translation, extension, start.crouching, start.shape);
+    world.debug_shape_cast(&collider, center - Vec3::Y * (height * 0.5), Dir3::Y, hull.step_height, Color::srgb(1.0, 1.0, 0.0));
+    let up = world
+        .cast_shape(&collider, center, Quat::default(), Dir3::Y, hull.step_height)
+        .map_or(hull.step_height, |hit| hit.distance.max(0.0));
+    if up <= f32::EPSILON {
+        return;
+    }
+    let mut stepped = start;
+    stepped.translation.y += up;
+    collision_and_slide(&mut stepped, config, dt, ground_cast_distance, carry, world);
+    let (collider, center, height) = body_collider(stepped.translation, extension, stepped.crouching, stepped.shape);
+    world.debug_shape_cast(&collider, center - Vec3::Y * (height * 0.5), Dir3::NEG_Y, up, Color::srgb(1.0, 1.0, 0.0));
+    match world.cast_shape(&collider, center, Quat::default(), Dir3::NEG_Y, up) {
+        Some(hit) if !is_walkable(hit.normal1, config) => return,
+        Some(hit) => {
+            stepped.translation.y -= hit.distance.max(0.0);
+            // Standing on the step now, whatever the lifted slide saw below it
+            if !start.grounded && stepped.landing.is_none() {
+                let impact_speed = (-start.velocity.y).max(0.0);
+                stepped.landing = Some(Landing { impact_speed, air_time: start.fall_timer });
+            }
+            stepped.grounded = true;
+            stepped.ground_normal = hit.normal1;
+            stepped.ground_entity = Some(hit.entity);
+        }
+        None => stepped.translation.y -= up,
+    }
+    let plain_distance = (state.translation - start.translation).xz().length_squared();
+    let stepped_distance = (stepped.translation - start.translation).xz().length_squared();
+    if stepped_distance <= plain_distance {
+        return;
+    }
+    // Keep the vertical velocity of the plain move, as Quake does
+    stepped.velocity.y = state.velocity.y;
+    let step = stepped.translation.y - start.translation.y;
+    if step > STEP_EVENT_MIN_HEIGHT && stepped.grounded {
+        stepped.stepped_up = Some(step);
+    }
+    *state = stepped;
+}
+
+/// Accelerates the player velocity in the given wish direction.
+///
+/// # Parameters
+/// - `velocity`: The player's velocity vector (mutated in-place).
+/// - `wish_dir`: The desired movement direction (normalized).
+/// - `wish_speed`: The desired speed in the wish direction.
+/// - `accel`: Acceleration rate.
+/// - `dt`: Delta time for this tick (seconds).
+pub fn accelerate(
+    velocity: &mut Vec3,
+    wish_dir: Vec3,
+    wish_speed: f32,
+    accel: f32,
+    dt: f32,
+) {
+    let current_speed = velocity.dot(wish_dir);
+    let add_speed = wish_speed - current_speed;
+
+    if add_speed <= 0.0 {
+        return;
+    }
+
+    let accel_speed = accel * wish_speed * dt;
+    let accel_speed = accel_speed.min(add_speed);
+
+    velocity.x += accel_speed * wish_dir.x;
+    velocity.z += accel_speed * wish_dir.z;
+}
+
+/// Applies friction to the player velocity when grounded.
+///
+/// # Parameters
+/// - `velocity`: The player's velocity vector (mutated in-place).
+/// - `config`: The movement configuration.
+/// - `dt`: Delta time for this tick (seconds).
+pub fn apply_friction(
+    velocity: &mut Vec3,
+    config: &MovementConfig,
+    dt: f32,
+) {
+    apply_friction_amount(velocity, config.friction, dt);
+}
+
+/// Applies the given friction coefficient to the horizontal velocity.
+fn apply_friction_amount(velocity: &mut Vec3, friction: f32, dt: f32) {
+    let speed = velocity.length();
+    if speed <= 0.0 {
+        return;
+    }
+
+    let drop = speed * friction * dt;
+    let new_speed = (speed - drop).max(0.0);
+
+    let speed_ratio = new_speed / speed;
+    velocity.x *= speed_ratio;
+    velocity.z *= speed_ratio;
+}
+
+/// Returns how far below the regular body the collide-and-slide collider reaches.
+///
+/// `StepMode::StepSlide` always uses the full hull down to the feet (minus the ground cast skin); otherwise the
+/// extended collider is used while airborne.
+fn body_extension(state: &MovementState, config: &MovementConfig, ground_cast_distance: Option<f32>) -> f32 {
+    let hull = state.shape.hull();
+    match config.step_mode {
+        StepMode::StepSlide => hull.step_height,
+        StepMode::Smoothed
+            if should_use_extended_collider(state.fall_timer, state.grounded, state.velocity, ground_cast_distance) =>
+        {
+            hull.extended_step_height()
+        }
+        StepMode::Smoothed => 0.0,
+    }
+}
+
+// Returns true if the player should use the extended collider for collision/slide and penetration correction.
+// This is true if the player has been airborne for a while (air_time > 0.15) and is not grounded.
+fn should_use_extended_collider(
+    air_time: f32,
+    grounded: bool,
+    _velocity: Vec3,
+    _ground_cast_distance: Option<f32>,
+) -> bool {
+    (air_time > 0.15) && !grounded
+}
+
+/// Collision worlds for unit tests.
+#[cfg(test)]
+pub(crate) mod test_world {
+    use super::*;
+
+    /// `CollisionWorld` made of infinite static planes, each given by its unit normal and its distance from the
+    /// origin along that normal, and of axis-aligned boxes for geometry with edges such as steps.
+    ///
+    /// Cast shapes are approximated by their axis-aligned bounding box, which is exact for boxes and for
+    /// axis-aligned planes. Each plane reports itself as `Entity::from_raw(index)`, and boxes follow the planes.
+    pub struct PlaneWorld {
+        pub planes: Vec<(Vec3, f32)>,
+        /// Minimum and maximum corner of each box.
+        pub boxes: Vec<(Vec3, Vec3)>,
+    }
+
+    impl PlaneWorld {
+        /// Creates a world from planes built with `PlaneWorld::plane`.
+        pub fn new(planes: impl IntoIterator<Item = (Vec3, f32)>) -> Self {
+            Self { planes: planes.into_iter().collect(), boxes: Vec::new() }
+        }
+
+        /// Adds the box spanning `min` to `max`.
+        pub fn with_box(mut self, min: Vec3, max: Vec3) -> Self {
+            self.boxes.push((min, max));
+            self
+        }
+
+        /// Returns the plane through `point` facing along `normal`.
+        pub fn plane(normal: Vec3, point: Vec3) -> (Vec3, f32) {
+            let normal = normal.normalize();
+            (normal, normal.dot(point))
+        }
+    }
+
+    impl CollisionWorld for PlaneWorld {
+        fn cast_shape(
+            &self,
+            shape: &Collider,
+            origin: Vec3,
+            rotation: Quat,
+            direction: Dir3,
+            max_distance: f32,
+        ) -> Option<ShapeHitData> {
+            self.shape_hits(shape, origin, rotation, direction, max_distance, 1).into_iter().next()
+        }
+
+        fn shape_hits(
+            &self,
+            shape: &Collider,
+            origin: Vec3,
+            rotation: Quat,
+            direction: Dir3,
+            max_distance: f32,
+            max_hits: u32,
+        ) -> Vec<ShapeHitData> {
+            let aabb = shape.aabb(origin, rotation);
+            let center = (aabb.min + aabb.max) * 0.5;
+            let half_extents = (aabb.max - aabb.min) * 0.5;
+            let mut hits: Vec<ShapeHitData> = self
+                .planes
+                .iter()
+                .enumerate()
+                .filter_map(|(index, &(normal, offset))| {
+                    let support = normal.abs().dot(half_extents);
+                    let separation = normal.dot(center) - offset - support;
+                    let approach = -normal.dot(*direction);
+                    // Overlapping shapes report the penetration depth as a negative distance
+                    let distance = if separation < 0.0 {
+                        separation
+                    } else if approach > 0.0 {
+                        separation / approach
+                    } else {
+                        return None;
+                    };
+                    if distance > max_distance {
+                        return None;
+                    }
+                    let point = center + *direction * distance.max(0.0) - normal * support;
+                    Some(ShapeHitData {
+                        entity: Entity::from_raw(index as u32),
+                        distance,
+                        point1: point,
+                        point2: point,
+                        normal1: normal,
+                        normal2: normal,
+                    })
+                })
+                .chain(self.boxes.iter().enumerate().filter_map(|(index, &(min, max))| {
+                    let (distance, normal) = box_hit(min - half_extents, max + half_extents, center, *direction)?;
+                    if distance > max_distance {
+                        return None;
+                    }
+                    let point = center + *direction * distance.max(0.0) - normal * normal.abs().dot(half_extents);
+                    Some(ShapeHitData {
+                        entity: Entity::from_raw((self.planes.len() + index) as u32),
+                        distance,
+                        point1: point,
+                        point2: point,
+                        normal1: normal,
+                        normal2: normal,
+                    })
+                }))
+                .collect();
+            hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+            hits.truncate(max_hits as usize);
+            hits
+        }
+    }
+
+    /// Casts a ray from `origin` along `direction` against the box from `min` to `max` (already grown by the cast
+    /// shape's half extents), returning the hit distance and normal.
+    ///
+    /// An origin inside the box reports the depth along the shallowest axis as a negative distance.
+    fn box_hit(min: Vec3, max: Vec3, origin: Vec3, direction: Vec3) -> Option<(f32, Vec3)> {
+        if origin.cmpgt(min).all() && origin.cmplt(max).all() {
+            let depths = [
+                (origin.x - min.x, Vec3::NEG_X),
+                (max.x - origin.x, Vec3::X),
+                (origin.y - min.y, Vec3::NEG_Y),
+                (max.y - origin.y, Vec3::Y),
+                (origin.z - min.z, Vec3::NEG_Z),
+                (max.z - origin.z, Vec3::Z),
+            ];
+            let (depth, normal) = depths.into_iter().min_by(|a, b| a.0.total_cmp(&b.0))?;
+            return Some((-depth, normal));
+        }
+        let mut enter = f32::NEG_INFINITY;
+        let mut exit = f32::INFINITY;
+        let mut normal = Vec3::ZERO;
+        for axis in 0..3 {
+            if direction[axis].abs() < 1e-8 {
+                if origin[axis] < min[axis] || origin[axis] > max[axis] {
+                    return None;
+                }
+                continue;
+            }
+            let to_min = (min[axis] - origin[axis]) / direction[axis];
+            let to_max = (max[axis] - origin[axis]) / direction[axis];
+            let (near, far) = if to_min < to_max { (to_min, to_max) } else { (to_max, to_min) };
+            if near > enter {
+                enter = near;
+                normal = Vec3::ZERO;
+                normal[axis] = -direction[axis].signum();
+            }
+            exit = exit.min(far);
+        }
+        (enter >= 0.0 && enter <= exit).then_some((enter, normal))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::player::movement::step::test_world::PlaneWorld;
+    use std::f32::consts::FRAC_1_SQRT_2;
+
+    fn grounded_state() -> MovementState {
+        MovementState::from_components(Vec3::ZERO, &CharacterVelocity(Vec3::ZERO), &Grounded(true), None, None)
+    }
+
+    fn walk(direction: Vec2, yaw_rotation: f32) -> MovementInput {
+        MovementInput { movement_direction: direction, movement_magnitude: 1.0, yaw_rotation, ..default() }
+    }
+
+    #[test]
+    fn players_accelerate_independently() {
+        let config = MovementConfig::default();
+        let inputs = [
+            walk(Vec2::Y, 0.0),
+            walk(Vec2::X, 0.0),
+            walk(Vec2::Y, std::f32::consts::FRAC_PI_2),
+            MovementInput::default(),
+        ];
+        let mut states = [grounded_state(); 4];
+        for _ in 0..32 {
+            for (state, input) in states.iter_mut().zip(&inputs) {
+                apply_input(state, input, &config, 1.0 / 64.0);
+            }
+        }
+        let [forward, right, turned, idle] = states.map(|state| state.velocity);
+        assert!(forward.z < -1.0 && forward.x.abs() < 1e-4, "forward: {forward}");
+        assert!(right.x > 1.0 && right.z.abs() < 1e-4, "right: {right}");
+        // Yawed 90° to the left, forward input moves towards -X
+        assert!(turned.x < -1.0 && turned.z.abs() < 1e-4, "turned: {turned}");
+        assert_eq!(idle, Vec3::ZERO);
+        // Each player only reacts to its own input: the same input gives the same speed whatever the others do
+        assert!((forward.length() - right.length()).abs() < 1e-4);
+        assert!((forward.length() - turned.length()).abs() < 1e-4);
+    }
+
+    #[test]
+    fn knockback_is_not_a_jump() {
+        let config = MovementConfig::default();
+        let mut state = grounded_state();
+        apply_impulse(&mut state, Vec3::Y * 5.0);
+        assert!(!state.grounded);
+        assert_eq!(state.just_jumped, f32::MAX);
+        assert_eq!(state.launch_timer, 0.0);
+        // Knocked off the ground, not walked off it: no coyote jump
+        advance_timers(&mut state, 1.0 / 64.0);
+        apply_input(&mut state, &MovementInput { jump_pressed: true, ..default() }, &config, 1.0 / 64.0);
+        assert_eq!(state.jumped, None);
+        // Landing from knockback doesn't enable a double jump
+        state.grounded = true;
+        advance_timers(&mut state, 1.0 / 64.0);
+        assert!(!state.landed_from_jump);
+    }
+
+    #[test]
+    fn landing_just_below_step_height_is_reported() {
+        let world = PlaneWorld::new([PlaneWorld::plane(Vec3::Y, Vec3::ZERO)]);
+        let config = MovementConfig::default();
+        let mut state =
+            MovementState { translation: Vec3::Y * 1.5, velocity: Vec3::NEG_Y * 0.2, fall_timer: 0.5, ..default() };
+        let step_height = state.shape.hull().step_height;
+        // Place the player so the ground is within the 1 cm band past the step height
+        let mut probe = state;
+        let distance = ground_and_step_normalization(&mut probe, &config, 1.0 / 64.0, &world).unwrap();
+        state.translation.y -= distance - (step_height + 0.005);
+        ground_and_step_normalization(&mut state, &config, 1.0 / 64.0, &world);
+        assert!(state.grounded);
+        assert_eq!(state.landing, Some(Landing { impact_speed: 0.2, air_time: 0.5 }));
+    }
+
+    #[test]
+    fn air_control_turns_a_few_degrees_per_tick() {
+        let config = MovementConfig::default();
+        let mut velocity = Vec3::new(10.0, 0.0, 0.0);
+        let wish_dir = Vec3::new(1.0, 0.0, 1.0).normalize();
+        air_control(&mut velocity, wish_dir, config.air_control, 1.0 / 64.0);
+        let turned = velocity.angle_between(Vec3::X).to_degrees();
+        assert!(turned > 0.5 && turned < 5.0, "turned: {turned}°");
+        assert!((velocity.length() - 10.0).abs() < 1e-4, "velocity: {velocity}");
+    }
+
+    /// Airborne box-shaped player at the origin moving with `velocity`.
+    fn sliding_state(velocity: Vec3) -> MovementState {
+        let mut state =
+            MovementState::from_components(Vec3::ZERO, &CharacterVelocity(velocity), &Grounded(false), None, None);
+        state.shape = PlayerShape::Aabb;
+        state
+    }
+
+    /// Config where a 45° ramp is walkable, so it is slid along its true plane.
+    fn ramp_config() -> MovementConfig {
+        MovementConfig { max_slope_angle: 50.0, ..default() }
+    }
+
+    #[test]
+    fn clip_stops_in_90_degree_corner() {
+        let planes = [Vec3::NEG_X, Vec3::NEG_Z];
+        let clipped = clip_velocity_to_planes(Vec3::new(10.0, 0.0, 10.0), &planes);
+        assert!(clipped.length() < 1e-4, "clipped: {clipped}");
+    }
+
+    #[test]
+    fn clip_slides_along_wall_and_ramp_crease() {
+        let ramp = Vec3::new(-FRAC_1_SQRT_2, FRAC_1_SQRT_2, 0.0);
+        let planes = [Vec3::NEG_Z, ramp];
+        let clipped = clip_velocity_to_planes(Vec3::new(10.0, 0.0, 10.0), &planes);
+        assert!((clipped - Vec3::new(5.0, 5.0, 0.0)).length() < 1e-4, "clipped: {clipped}");
+        for plane in planes {
+            assert!(clipped.dot(plane) >= -1e-4);
+        }
+    }
+
+    #[test]
+    fn clip_follows_crease_of_walls_at_45_degrees() {
+        let walls = [Vec3::new(-FRAC_1_SQRT_2, 0.0, FRAC_1_SQRT_2), Vec3::NEG_Z];
+        let clipped = clip_velocity_to_planes(Vec3::new(10.0, -2.0, 0.0), &walls);
+        // The crease of two vertical walls is vertical: horizontal motion stops, falling continues
+        assert!((clipped - Vec3::new(0.0, -2.0, 0.0)).length() < 1e-4, "clipped: {clipped}");
+    }
+
+    #[test]
+    fn clip_stops_against_three_conflicting_planes() {
+        let planes = [Vec3::Y, Vec3::NEG_X, Vec3::NEG_Z];
+        assert_eq!(clip_velocity_to_planes(Vec3::new(1.0, -1.0, 1.0), &planes), Vec3::ZERO);
+    }
+
+    #[test]
+    fn slide_stops_in_90_degree_corner() {
+        let world = PlaneWorld::new([
+            PlaneWorld::plane(Vec3::NEG_X, Vec3::new(1.0, 0.0, 0.0)),
+            PlaneWorld::plane(Vec3::NEG_Z, Vec3::new(0.0, 0.0, 1.0)),
+        ]);
+        let mut state = sliding_state(Vec3::new(10.0, 0.0, 10.0));
+        let half_width = state.shape.hull().radius;
+        collision_and_slide(&mut state, &MovementConfig::default(), 0.5, None, Vec3::ZERO, &world);
+        assert!(state.velocity.length() < 1e-4, "velocity: {}", state.velocity);
+        assert!(state.translation.x <= 1.0 - half_width + 1e-3, "translation: {}", state.translation);
+        assert!(state.translation.z <= 1.0 - half_width + 1e-3, "translation: {}", state.translation);
+        assert!(state.translation.x > 0.5 && state.translation.z > 0.5, "translation: {}", state.translation);
+    }
+
+    #[test]
+    fn slide_follows_wall_and_ramp_crease() {
+        let ramp = Vec3::new(-FRAC_1_SQRT_2, FRAC_1_SQRT_2, 0.0);
+        let world = PlaneWorld::new([
+            PlaneWorld::plane(Vec3::NEG_Z, Vec3::new(0.0, 0.0, 1.0)),
+            PlaneWorld::plane(ramp, Vec3::new(2.0, 0.0, 0.0)),
+        ]);
+        let mut state = sliding_state(Vec3::new(10.0, 0.0, 10.0));
+        let half_width = state.shape.hull().radius;
+        collision_and_slide(&mut state, &ramp_config(), 0.5, None, Vec3::ZERO, &world);
+        // Velocity runs up the seam between the wall and the ramp
+        assert!((state.velocity - Vec3::new(5.0, 5.0, 0.0)).length() < 1e-3, "velocity: {}", state.velocity);
+        assert!(state.translation.y > 1.0, "translation: {}", state.translation);
+        assert!(state.translation.z <= 1.0 - half_width + 1e-3, "translation: {}", state.translation);
+        assert_eq!(state.wall_normal, Vec3::NEG_Z);
+    }
+
+    #[test]
+    fn wall_run_keeps_contact_with_parallel_wall() {
+        let world = PlaneWorld::new([PlaneWorld::plane(Vec3::X, Vec3::new(-0.45, 0.0, 0.0))]);
+        let config = MovementConfig::default();
+        let mut state = sliding_state(Vec3::new(0.0, 0.0, -8.0));
+        state.wall_running = true;
+        state.wall_normal = Vec3::X;
+        state.wall_timer = 0.0;
+        for _ in 0..32 {
+            advance_wall_timers(&mut state, 1.0 / 64.0);
+            kinematic_step(&mut state, &config, 1.0 / 64.0, &world);
+        }
+        // The run never moves into the wall, so only the probe keeps the contact fresh
+        assert_eq!(state.wall_timer, 0.0);
+        assert_eq!(state.wall_entity, Some(Entity::from_raw(0)));
+        assert!(state.wall_hit.is_none());
+    }
+
+    #[test]
+    fn slide_stops_between_walls_at_45_degrees() {
+        let wall = Vec3::new(-FRAC_1_SQRT_2, 0.0, FRAC_1_SQRT_2);
+        let world = PlaneWorld::new([
+            PlaneWorld::plane(wall, Vec3::new(2.0, 0.0, 1.0)),
+            PlaneWorld::plane(Vec3::NEG_Z, Vec3::new(0.0, 0.0, 1.0)),
+        ]);
+        let mut state = sliding_state(Vec3::new(10.0, -2.0, 0.0));
+        let half_width = state.shape.hull().radius;
+        collision_and_slide(&mut state, &MovementConfig::default(), 0.5, None, Vec3::ZERO, &world);
+        // Wedged into the acute corner, the player only keeps sliding down the vertical crease
+        assert!((state.velocity - Vec3::new(0.0, -2.0, 0.0)).length() < 1e-3, "velocity: {}", state.velocity);
+        assert!(state.translation.y < -0.5, "translation: {}", state.translation);
+        assert!(state.translation.z <= 1.0 - half_width + 1e-3, "translation: {}", state.translation);
+        assert!(state.translation.x > 0.0, "translation: {}", state.translation);
+    }
+
+    #[test]
+    fn step_slide_climbs_stairs_without_losing_speed() {
+        let riser = 1.0;
+        let step = 0.3;
+        let world = PlaneWorld::new([])
+            .with_box(Vec3::new(-10.0, -1.0, -10.0), Vec3::new(riser, 0.0, 10.0))
+            .with_box(Vec3::new(riser, -1.0, -10.0), Vec3::new(10.0, step, 10.0));
+        let config = MovementConfig { step_mode: StepMode::StepSlide, ..default() };
+        let mut state = MovementState {
+            translation: Vec3::Y * 1.5,
+            velocity: Vec3::X * 5.0,
+            grounded: true,
+            shape: PlayerShape::Aabb,
+            ..default()
+        };
+        // Stand exactly at step height above the floor, as the ground snap leaves the player
+        let step_height = state.shape.hull().step_height;
+        let mut probe = state;
+        let distance = ground_and_step_normalization(&mut probe, &config, 1.0 / 64.0, &world).unwrap();
+        state.translation.y -= distance - step_height;
+        let start_height = state.translation.y;
+
+        let mut steps = 0;
+        for _ in 0..48 {
+            state.clear_events();
+            kinematic_step(&mut state, &config, 1.0 / 64.0, &world);
+            if state.stepped_up.is_some() {
+                steps += 1;
+                assert!(state.grounded);
+                assert_eq!(state.ground_normal, Vec3::Y);
+                assert_eq!(state.ground_entity, Some(Entity::from_raw(1)));
+            }
+        }
+        assert_eq!(steps, 1);
+        assert!(state.translation.x > riser + 1.0, "translation: {}", state.translation);
+        assert!((state.translation.y - (start_height + step)).abs() < 0.01, "translation: {}", state.translation);
+        assert!(state.grounded);
+        assert!((state.velocity - Vec3::X * 5.0).length() < 1e-4, "velocity: {}", state.velocity);
+    }
+}
@@ -0,0 +1,220 @@
+//! Movement triggers: Quake-style jump pads, teleporters and ladders.
+//!
+//! All are sensor colliders. Every fixed tick the players currently overlapping a trigger (from avian's
+//! `CollidingEntities`) are launched or moved by writing `CharacterVelocity`, the player position, `Grounded` and
+//! `JustJumped` directly, after the jump and fall timers advance and before `movement` runs. Ladders only flag the
+//! player in `LadderContact`; the climbing itself happens in `step::apply_input`.
+use avian3d::prelude::*;
+use bevy::prelude::*;
+
+use crate::game::player::movement::core::{movement, CharacterVelocity, Grounded, MovementBackend, MovementConfig};
+use crate::game::player::movement::jump::{update_jump_and_fall_timers, JustJumped};
+
+/// Launches players so that the apex of their arc is at `target`.
+///
+/// Like Quake's `trigger_push`, the target marks the top of the parabola rather than the landing point; place it
+/// above the landing spot. The collider on this entity is made a sensor.
+#[derive(Component, Debug, Clone, Default, Reflect, serde::Serialize, serde::Deserialize)]
+#[reflect(Component)]
+#[require(Sensor, CollidingEntities)]
+pub struct JumpPad {
+    /// World position of the top of the launch arc.
+    pub target: Vec3,
+}
+
+/// How a teleporter treats the velocity of the player it moves.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Reflect, serde::Serialize, serde::Deserialize)]
+pub enum TeleportVelocity {
+    /// Keep the world-space velocity unchanged.
+    Preserve,
+    /// Keep the speed but rotate the horizontal velocity by the yaw change, so the player exits the way the
+    /// destination faces.
+    #[default]
+    Redirect,
+}
+
+/// Moves players to `destination`, optionally turning them to face `destination_yaw`.
+///
+/// The collider on this entity is made a sensor.
+#[derive(Component, Debug, Clone, Default, Reflect, serde::Serialize, serde::Deserialize)]
+#[reflect(Component)]
+#[require(Sensor, CollidingEntities)]
+pub struct Teleporter {
+    /// World position the player is moved to (player origin, not feet).
+    pub destination: Vec3,
+    /// Yaw (radians) the player faces after teleporting. `None` keeps the current facing.
+    pub destination_yaw: Option<f32>,
+    /// What happens to the player's velocity.
+    pub velocity: TeleportVelocity,
+}
+
+/// Makes the volume climbable. Players inside it climb instead of walking (see `MovementConfig::ladder_climb_speed`).
+///
+/// The collider on this entity is made a sensor; place it in front of the climbable surface.
+#[derive(Component, Debug, Clone, Default, Reflect, serde::Serialize, serde::Deserialize)]
+#[reflect(Component)]
+#[require(Sensor, CollidingEntities)]
+pub struct Ladder {
+    /// World direction pointing away from the climbable surface, used to push players off on a dismount jump.
+    /// `Vec3::ZERO` pushes players backwards from where they are looking.
+    pub normal: Vec3,
+}
+
+/// Ladder state of a player, updated by `update_ladder_contacts`.
+#[derive(Component, Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct LadderContact {
+    /// Whether the player is inside a `Ladder` volume.
+    pub on_ladder: bool,
+    /// `Ladder::normal` of the ladder the player is on.
+    pub normal: Vec3,
+}
+
+/// Sent when a player is teleported.
+///
+/// The view yaw comes from the client's input, so the camera/input layer should apply `yaw_delta` to it.
+#[derive(Event, Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Teleported {
+    /// The teleported player.
+    pub entity: Entity,
+    /// The teleporter that was used.
+    pub teleporter: Entity,
+    /// Yaw change (radians) applied to the player.
+    pub yaw_delta: f32,
+}
+
+/// Registers trigger components, events and systems before the core movement systems.
+pub struct MovementTriggersPlugin;
+
+impl Plugin for MovementTriggersPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Teleported>()
+            .register_type::<JumpPad>()
+            .register_type::<Teleporter>()
+            .register_type::<Ladder>()
+            .add_systems(
+                FixedUpdate,
+                (apply_jump_pads, apply_teleporters, update_ladder_contacts)
+                    .after(update_jump_and_fall_timers)
+                    .before(movement),
+            );
+    }
+}
+
+/// Returns the initial velocity that takes a body from `origin` to the apex at `target` under `gravity`.
+///
+/// The vertical component is chosen so the body peaks exactly at the target height; the horizontal component
+/// covers the horizontal distance in the time it takes to get there. Returns `Vec3::ZERO` if the target is not
+/// above `origin` or `gravity` is not positive.
+pub fn launch_velocity(origin: Vec3, target: Vec3, gravity: f32) -> Vec3 {
+    let height = target.y - origin.y;
+    if height <= 0.0 || gravity <= 0.0 {
+        return Vec3::ZERO;
+    }
+    let time = (2.0 * height / gravity).sqrt();
+    let horizontal = Vec3::new(target.x - origin.x, 0.0, target.z - origin.z) / time;
+    Vec3::new(horizontal.x, gravity * time, horizontal.z)
+}
+
+/// Launches every player overlapping a `JumpPad` towards its target.
+///
+/// - Sets `CharacterVelocity` from `launch_velocity` and `MovementConfig::gravity`.
+/// - Ungrounds the player and resets `JustJumped::since_launch` so the ground snap doesn't eat the launch. Like
+///   knockback, a launch is not a jump, so it doesn't allow double jumps on landing.
+///
+/// Should be run in `FixedUpdate` after `update_jump_and_fall_timers` and before `movement`.
+pub fn apply_jump_pads(
+    pads: Query<(&JumpPad, &CollidingEntities)>,
+    colliders: Query<&ColliderOf>,
+    mut players: Query<
+        (&Transform, Option<&Position>, &mut CharacterVelocity, &mut Grounded, Option<&mut JustJumped>),
+        With<crate::game::player::Player>,
+    >,
+    config: Res<MovementConfig>,
+    backend: Option<Res<MovementBackend>>,
+) {
+    let backend = backend.map_or_else(MovementBackend::default, |b| *b);
+    for (pad, colliding) in pads.iter() {
+        for &collider in colliding.iter() {
+            let body = colliders.get(collider).map_or(collider, |collider_of| collider_of.body);
+            let Ok((transform, position, mut velocity, mut grounded, just_jumped)) = players.get_mut(body) else {
+                continue;
+            };
+            let origin = backend.translation(transform, position);
+            let launch = launch_velocity(origin, pad.target, config.gravity);
+            if launch == Vec3::ZERO {
+                continue;
+            }
+            velocity.0 = launch;
+            grounded.0 = false;
+            if let Some(mut just_jumped) = just_jumped {
+                just_jumped.since_launch = 0.0;
+            }
+        }
+    }
+}
+
+/// Moves every player overlapping a `Teleporter` to its destination.
+///
+/// - Writes the position through the selected `MovementBackend`.
+/// - Turns the player to `destination_yaw` and adjusts velocity according to `TeleportVelocity`.
+/// - Ungrounds the player and sends `Teleported`.
+///
+/// Should be run in `FixedUpdate` after `update_jump_and_fall_timers` and before `movement`.
+pub fn apply_teleporters(
+    teleporters: Query<(Entity, &Teleporter, &CollidingEntities)>,
+    colliders: Query<&ColliderOf>,
+    mut players: Query<
+        (&mut Transform, Option<&mut Position>, &mut CharacterVelocity, &mut Grounded),
+        With<crate::game::player::Player>,
+    >,
+    mut teleported: EventWriter<Teleported>,
+    backend: Option<Res<MovementBackend>>,
+) {
+    let backend = backend.map_or_else(MovementBackend::default, |b| *b);
+    for (teleporter_entity, teleporter, colliding) in teleporters.iter() {
+        for &collider in colliding.iter() {
+            let body = colliders.get(collider).map_or(collider, |collider_of| collider_of.body);
+            let Ok((mut transform, mut position, mut velocity, mut grounded)) = players.get_mut(body) else {
+                continue;
+            };
+            let current_yaw = transform.rotation.to_euler(EulerRot::YXZ).0;
+            let yaw_delta = teleporter.destination_yaw.map_or(0.0, |yaw| yaw - current_yaw);
+            backend.write_translation(teleporter.destination, &mut transform, position.as_deref_mut());
+            transform.rotation = Quat::from_rotation_y(yaw_delta) * transform.rotation;
+            if teleporter.velocity == TeleportVelocity::Redirect {
+                velocity.0 = Quat::from_rotation_y(yaw_delta) * velocity.0;
+            }
+            grounded.0 = false;
+            teleported.write(Teleported { entity: body, teleporter: teleporter_entity, yaw_delta });
+        }
+    }
+}
+
+/// Flags every player overlapping a `Ladder` in `LadderContact`, and clears the flag for everyone else.
+///
+/// Should be run in `FixedUpdate` after `update_jump_and_fall_timers` and before `movement`.
+pub fn update_ladder_contacts(
+    mut commands: Commands,
+    ladders: Query<(&Ladder, &CollidingEntities)>,
+    colliders: Query<&ColliderOf>,
+    mut players: Query<(Entity, Option<&mut LadderContact>), With<crate::game::player::Player>>,
+) {
+    let mut touching: std::collections::HashMap<Entity, Vec3> = std::collections::HashMap::new();
+    for (ladder, colliding) in ladders.iter() {
+        for &collider in colliding.iter() {
+            let body = colliders.get(collider).map_or(collider, |collider_of| collider_of.body);
+            touching.insert(body, ladder.normal);
+        }
+    }
+    for (entity, contact) in players.iter_mut() {
+        let normal = touching.get(&entity).copied();
+        let new_contact = LadderContact { on_ladder: normal.is_some(), normal: normal.unwrap_or(Vec3::ZERO) };
+        if let Some(mut contact) = contact {
+            if *contact != new_contact {
+                *contact = new_contact;
+            }
+        } else if new_contact.on_ladder {
+            commands.entity(entity).insert(new_contact);
+        }
+    }
+}
@@ -0,0 +1,76 @@
+//! Wall jumping and wall running for the player. Components and systems for wall contact and wall-run state.
+//!
+//! This module defines components and systems for tracking wall contacts (recorded by `collision_and_slide`) and
+//! wall-run timing. The wall jump and wall run themselves are applied in `step::apply_input`.
+use bevy::prelude::*;
+
+use crate::game::player::movement::core::movement;
+use crate::game::player::movement::jump::update_jump_and_fall_timers;
+use crate::game::player::movement::step::{advance_wall_timers, MovementState};
+
+/// Component describing the last wall the player touched.
+///
+/// Updated by `kinematic_movement` from the collide-and-slide hits. Floors, surf ramps and ceilings are not walls.
+#[derive(Component, Debug, Clone, Reflect, serde::Serialize, serde::Deserialize)]
+#[reflect(Component)]
+pub struct WallContact {
+    /// Horizontal normal of the wall, pointing away from it.
+    pub normal: Vec3,
+    /// Entity of the wall's collider.
+    #[serde(skip)]
+    pub entity: Option<Entity>,
+    /// Seconds since the wall was touched. Set to f32::MAX once a wall jump used the contact.
+    pub timer: f32,
+}
+impl Default for WallContact {
+    fn default() -> Self {
+        Self { normal: Vec3::ZERO, entity: None, timer: f32::MAX }
+    }
+}
+/// Component for tracking the wall-run state.
+///
+/// Used to limit wall runs to `MovementConfig::wall_run_max_duration` between landings.
+#[derive(Component, Debug, Clone, Default, Reflect, serde::Serialize, serde::Deserialize)]
+#[reflect(Component)]
+pub struct WallRunTimer {
+    /// Whether the player is currently wall running.
+    pub running: bool,
+    /// Seconds spent wall running since the player was last grounded.
+    pub timer: f32,
+}
+
+/// Registers wall components and schedules the wall timers right after the jump and fall timers.
+pub struct WallPlugin;
+
+impl Plugin for WallPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<WallContact>()
+            .register_type::<WallRunTimer>()
+            .add_systems(FixedUpdate, update_wall_timers.after(update_jump_and_fall_timers).before(movement));
+    }
+}
+
+/// Updates the wall contact and wall-run timers for all players.
+///
+/// - Increments `WallContact.timer` if less than f32::MAX.
+/// - Increments `WallRunTimer.timer` while wall running.
+///
+/// Wraps `step::advance_wall_timers`. Should be run in `FixedUpdate` before movement logic.
+pub fn update_wall_timers(
+    mut query: Query<(Option<&mut WallContact>, Option<&mut WallRunTimer>), With<crate::game::player::Player>>,
+    time: Res<Time>,
+) {
+    for (wall_contact, wall_run) in query.iter_mut() {
+        let mut state = MovementState::default();
+        state.wall_timer = wall_contact.as_ref().map_or(f32::MAX, |w| w.timer);
+        state.wall_running = wall_run.as_ref().is_some_and(|w| w.running);
+        state.wall_run_timer = wall_run.as_ref().map_or(0.0, |w| w.timer);
+        advance_wall_timers(&mut state, time.delta_secs());
+        if let Some(mut wall_contact) = wall_contact {
+            wall_contact.timer = state.wall_timer;
+        }
+        if let Some(mut wall_run) = wall_run {
+            wall_run.timer = state.wall_run_timer;
+        }
+    }
+}